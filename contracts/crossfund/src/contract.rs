@@ -1,29 +1,36 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult};
+use cosmwasm_std::{
+    Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, Uint128,
+};
 
 use cw2::set_contract_version;
 
 use crate::execute::{
-    accept_application, approve_application, fund_proposal_native, register_ica,
-    submit_application, submit_proposal, verify_application,
+    accept_application, approve_application, clear_errors, fund_fee_reserve, fund_proposal_native, refund_quarantined,
+    register_ica, retry_due, retry_pending, schedule_retries, set_connection_chain, sponsor_queries, submit_application, submit_proposal,
+    update_conflict_rules, update_fees, withdraw_registration_fees, withdraw_funds, register_auditor, unbond_auditor, claim_auditor_bond,
+    update_bond_rules, raise_dispute, resolve_dispute, expire_application, set_min_reputation, open_selection, select_applications, open_bounty, submit_deliverable, request_extension, vote_extension, post_update, comment_update, update_query_period, verify_application,
 };
 use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, NeutronResponse, QueryMsg};
 use crate::query::{
     query_acknowledgement_result, query_address_funds, query_all_proposals, query_errors_queue,
     query_interchain_address, query_interchain_address_contract, query_proposal,
+    query_proposal_summaries, query_application, query_applications, query_application_funding,
+    query_applications_by_participant, query_simulate_payout, query_auditor, query_auditors,
+    query_dispute, query_disputes, query_reputation, query_extension_request, query_updates,
+    query_config, query_deposit_addresses, query_fee_reserve, query_registration_fees, query_ibc_fee, query_pending_operations, query_proposal_queries, query_quarantined_deposits, query_reconciliation,
 };
 use crate::sudo::{
-    prepare_sudo_payload, sudo_error, sudo_open_ack, sudo_timeout, sudo_tx_query_result,
+    prepare_sudo_payload, save_query_id, sudo_error, sudo_kv_query_result, sudo_open_ack,
+    sudo_response, sudo_timeout, sudo_tx_query_result,
 };
-use crate::temp::register_transfers_query;
 
 use neutron_sdk::{bindings::query::NeutronQuery, sudo::msg::SudoMsg, NeutronResult};
 
-use crate::storage::SUDO_PAYLOAD_REPLY_ID;
-
-// Default timeout for SubmitTX is two weeks
-//const DEFAULT_TIMEOUT_SECONDS: u64 = 60 * 60 * 24 * 7 * 2;
+use crate::storage::{
+    migrate_applications, migrate_custody_funds, reindex_errors, Config, CONFIG, DEFAULT_QUERY_DEPOSIT, REGISTER_QUERY_REPLY_ID, SUDO_PAYLOAD_REPLY_ID_START,
+};
 
 const CONTRACT_NAME: &str = concat!("crates.io:neutron-sdk__", env!("CARGO_PKG_NAME"));
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -32,11 +39,29 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
+    info: MessageInfo,
+    msg: InstantiateMsg,
 ) -> NeutronResponse {
     deps.api.debug("WASMDEBUG: instantiate");
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender,
+    };
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            admin,
+            registration_fee: msg.registration_fee.unwrap_or_default(),
+            query_deposit: msg
+                .query_deposit
+                .unwrap_or(Uint128::new(DEFAULT_QUERY_DEPOSIT)),
+            conflict_rules: msg.conflict_rules.unwrap_or_default(),
+            bond_rules: msg.bond_rules.unwrap_or_default(),
+        },
+    )?;
+
     Ok(Response::default())
 }
 
@@ -50,17 +75,26 @@ pub fn execute(
     deps.api
         .debug(format!("WASMDEBUG: execute: received msg: {:?}", msg).as_str());
     match msg {
-        ExecuteMsg::SubmitProposal { title, description } => {
-            submit_proposal(deps.storage, title, description)
-        }
+        ExecuteMsg::SubmitProposal {
+            title,
+            description,
+            tags,
+        } => submit_proposal(
+            deps.storage,
+            info.sender,
+            title,
+            description,
+            tags.unwrap_or_default(),
+        ),
         ExecuteMsg::SubmitApplication {
             proposal_id,
             application,
-        } => submit_application(deps.storage, env, info.sender, proposal_id, application),
+        } => submit_application(deps, env, info.sender, proposal_id, application),
         ExecuteMsg::FundProposal {
             auto_agree,
             proposal_id,
-        } => fund_proposal_native(deps.storage, info, proposal_id, auto_agree),
+            require_registered_auditors,
+        } => fund_proposal_native(deps.storage, info, proposal_id, auto_agree, require_registered_auditors),
         ExecuteMsg::ApproveApplication {
             proposal_id,
             application_sender,
@@ -69,7 +103,7 @@ pub fn execute(
         ExecuteMsg::RegisterICA {
             proposal_id,
             connection_id,
-        } => register_ica(deps.storage, env, connection_id, proposal_id),
+        } => register_ica(deps.storage, env, info, connection_id, proposal_id),
         ExecuteMsg::AcceptApplication {
             proposal_id,
             application_sender,
@@ -81,22 +115,176 @@ pub fn execute(
             stop_at: _,
         } => verify_application(deps.storage, info.sender, proposal_id, application_sender),
 
-        ExecuteMsg::TempRegister {
+        ExecuteMsg::RefundQuarantined { id } => refund_quarantined(deps, env, info, id),
+
+        ExecuteMsg::WithdrawFunds {} => withdraw_funds(deps.storage, env, info),
+
+        ExecuteMsg::RetryPending { id } => retry_pending(deps, env, info, id),
+
+        ExecuteMsg::RetryDue { limit } => retry_due(deps, env, info, limit),
+
+        ExecuteMsg::ScheduleRetries { period } => {
+            schedule_retries(deps.storage, env, info.sender, period)
+        }
+
+        ExecuteMsg::ClearErrors { up_to } => clear_errors(deps.storage, info.sender, up_to),
+
+        ExecuteMsg::FundFeeReserve {} => fund_fee_reserve(deps.storage, info),
+
+        ExecuteMsg::SponsorQueries {
+            proposal_id,
+            connection_id,
+        } => sponsor_queries(deps.storage, info, proposal_id, connection_id),
+
+        ExecuteMsg::UpdateFees {
+            registration_fee,
+            query_deposit,
+        } => update_fees(deps.storage, info.sender, registration_fee, query_deposit),
+
+        ExecuteMsg::WithdrawRegistrationFees { recipient } => {
+            withdraw_registration_fees(deps, info.sender, recipient)
+        }
+
+        ExecuteMsg::UpdateConflictRules { rules } => update_conflict_rules(deps.storage, info.sender, rules),
+
+        ExecuteMsg::RegisterAuditor { profile_uri } => register_auditor(deps.storage, info, profile_uri),
+
+        ExecuteMsg::UnbondAuditor {} => unbond_auditor(deps.storage, env, info.sender),
+
+        ExecuteMsg::ClaimAuditorBond {} => claim_auditor_bond(deps.storage, env, info.sender),
+
+        ExecuteMsg::UpdateBondRules { rules } => update_bond_rules(deps.storage, info.sender, rules),
+
+        ExecuteMsg::SubmitDeliverable {
+            proposal_id,
+            application_sender,
+            uri,
+            content_hash,
+        } => submit_deliverable(deps.storage, env, info.sender, proposal_id, application_sender, uri, content_hash),
+
+        ExecuteMsg::RequestExtension {
+            proposal_id,
+            application_sender,
+            deliver_by,
+            reason,
+        } => request_extension(deps.storage, env, info.sender, proposal_id, application_sender, deliver_by, reason),
+
+        ExecuteMsg::VoteExtension {
+            proposal_id,
+            application_sender,
+            approve,
+        } => vote_extension(deps.storage, env, info.sender, proposal_id, application_sender, approve),
+
+        ExecuteMsg::PostUpdate {
+            proposal_id,
+            application_sender,
+            uri,
+            summary,
+        } => post_update(deps.storage, env, info.sender, proposal_id, application_sender, uri, summary),
+
+        ExecuteMsg::CommentUpdate {
+            proposal_id,
+            application_sender,
+            update_id,
+            comment,
+        } => comment_update(deps.storage, env, info.sender, proposal_id, application_sender, update_id, comment),
+
+        ExecuteMsg::ExpireApplication {
+            proposal_id,
+            application_sender,
+        } => expire_application(deps.storage, env, proposal_id, application_sender),
+
+        ExecuteMsg::SetMinReputation {
+            proposal_id,
+            requirement,
+        } => set_min_reputation(deps.storage, info.sender, proposal_id, requirement),
+
+        ExecuteMsg::OpenSelection {
+            proposal_id,
+            ends,
+            winners,
+        } => open_selection(deps.storage, env, info.sender, proposal_id, ends, winners),
+
+        ExecuteMsg::SelectApplications { proposal_id } => select_applications(deps.storage, env, proposal_id),
+
+        ExecuteMsg::OpenBounty {
+            proposal_id,
+            auditors,
+        } => open_bounty(deps, info.sender, proposal_id, auditors),
+
+        ExecuteMsg::RaiseDispute {
+            proposal_id,
+            application_sender,
+            reason,
+        } => raise_dispute(deps.storage, info.sender, proposal_id, application_sender, reason),
+
+        ExecuteMsg::ResolveDispute { id, upheld } => resolve_dispute(deps.storage, info.sender, id, upheld),
+
+        ExecuteMsg::SetConnectionChain {
             connection_id,
-            recipient,
+            chain_id,
+        } => set_connection_chain(deps.storage, info.sender, connection_id, chain_id),
+
+        ExecuteMsg::UpdateQueryPeriod {
+            proposal_id,
+            query_id,
+            update_period,
+        } => update_query_period(
+            deps.storage,
+            info.sender,
+            proposal_id,
+            query_id,
             update_period,
-            min_height,
-        } => register_transfers_query(connection_id, recipient, update_period, min_height),
+        ),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps<NeutronQuery>, env: Env, msg: QueryMsg) -> NeutronResult<Binary> {
     match msg {
-        QueryMsg::AllProposals {} => query_all_proposals(deps.storage),
+        QueryMsg::AllProposals {
+            start_after,
+            limit,
+            order,
+            filter,
+        } => query_all_proposals(deps, start_after, limit, order, filter),
+
+        QueryMsg::ProposalSummaries {
+            start_after,
+            limit,
+            order,
+            filter,
+        } => query_proposal_summaries(deps, start_after, limit, order, filter),
 
         QueryMsg::Proposal { proposal_id } => query_proposal(deps.storage, proposal_id),
 
+        QueryMsg::Application {
+            proposal_id,
+            sender,
+        } => query_application(deps, proposal_id, sender),
+
+        QueryMsg::Applications {
+            proposal_id,
+            start_after,
+            limit,
+        } => query_applications(deps, proposal_id, start_after, limit),
+
+        QueryMsg::ApplicationFunding {
+            proposal_id,
+            sender,
+        } => query_application_funding(deps, proposal_id, sender),
+
+        QueryMsg::SimulatePayout {
+            proposal_id,
+            application_sender,
+        } => query_simulate_payout(deps, proposal_id, application_sender),
+
+        QueryMsg::ApplicationsByParticipant {
+            address,
+            start_after,
+            limit,
+        } => query_applications_by_participant(deps, address, start_after, limit),
+
         QueryMsg::AddressFunds {
             address,
             skip_locked,
@@ -107,16 +295,74 @@ pub fn query(deps: Deps<NeutronQuery>, env: Env, msg: QueryMsg) -> NeutronResult
             proposal_id,
         } => query_interchain_address(deps, env, connection_id, proposal_id),
 
-        QueryMsg::InterchainAccountAddressFromContract { proposal_id } => {
-            query_interchain_address_contract(deps, env, proposal_id)
+        QueryMsg::InterchainAccountAddressFromContract {
+            proposal_id,
+            connection_id,
+        } => query_interchain_address_contract(deps, env, proposal_id, connection_id),
+
+        QueryMsg::DepositAddresses { proposal_id } => {
+            query_deposit_addresses(deps.storage, proposal_id)
         }
 
         QueryMsg::AcknowledgementResult {
             sequence_id,
             proposal_id,
-        } => query_acknowledgement_result(deps, env, sequence_id, proposal_id),
+            connection_id,
+        } => query_acknowledgement_result(deps, env, sequence_id, proposal_id, connection_id),
+
+        QueryMsg::ErrorsQueue {
+            start_after,
+            limit,
+            kind,
+        } => query_errors_queue(deps.storage, start_after, limit, kind),
+
+        QueryMsg::ProposalQueries { proposal_id } => {
+            query_proposal_queries(deps.storage, proposal_id)
+        }
+
+        QueryMsg::Config {} => query_config(deps.storage),
+
+        QueryMsg::Reconciliation { proposal_id } => query_reconciliation(deps, proposal_id),
+
+        QueryMsg::QuarantinedDeposits { start_after, limit } => {
+            query_quarantined_deposits(deps.storage, start_after, limit)
+        }
+
+        QueryMsg::FeeReserve {} => query_fee_reserve(deps.storage),
+        QueryMsg::RegistrationFees {} => query_registration_fees(deps.storage),
+
+        QueryMsg::PendingOperations {
+            start_after,
+            limit,
+            stuck_only,
+        } => query_pending_operations(deps.storage, start_after, limit, stuck_only.unwrap_or(false)),
+
+        QueryMsg::IbcFee {
+            channel_id,
+            sequence,
+        } => query_ibc_fee(deps.storage, channel_id, sequence),
+
+        QueryMsg::Auditor { address } => query_auditor(deps, address),
+
+        QueryMsg::Auditors { start_after, limit } => query_auditors(deps, start_after, limit),
+
+        QueryMsg::ExtensionRequest {
+            proposal_id,
+            application_sender,
+        } => query_extension_request(deps, env, proposal_id, application_sender),
 
-        QueryMsg::ErrorsQueue {} => query_errors_queue(deps),
+        QueryMsg::Updates {
+            proposal_id,
+            application_sender,
+            start_after,
+            limit,
+        } => query_updates(deps, proposal_id, application_sender, start_after, limit),
+
+        QueryMsg::Reputation { address } => query_reputation(deps, address),
+
+        QueryMsg::Dispute { id } => query_dispute(deps.storage, id),
+
+        QueryMsg::Disputes { start_after, limit } => query_disputes(deps.storage, start_after, limit),
     }
 }
 
@@ -146,11 +392,13 @@ pub fn sudo(deps: DepsMut<NeutronQuery>, env: Env, msg: SudoMsg) -> NeutronRespo
             data,
         } => sudo_tx_query_result(deps, env, query_id, height, data),
 
-        SudoMsg::Error { request, details } => sudo_error(deps, request, details),
+        SudoMsg::Response { request, data } => sudo_response(deps, env, request, data),
+
+        SudoMsg::KVQueryResult { query_id } => sudo_kv_query_result(deps, env, query_id),
 
-        SudoMsg::Timeout { request } => sudo_timeout(deps.storage, request),
+        SudoMsg::Error { request, details } => sudo_error(deps, env, request, details),
 
-        _ => Ok(Response::default()),
+        SudoMsg::Timeout { request } => sudo_timeout(deps.storage, env, request),
     }
 }
 
@@ -160,7 +408,8 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
         .debug(format!("WASMDEBUG: reply msg: {:?}", msg).as_str());
 
     match msg.id {
-        SUDO_PAYLOAD_REPLY_ID => prepare_sudo_payload(deps, env, msg),
+        REGISTER_QUERY_REPLY_ID => save_query_id(deps, env, msg),
+        id if id >= SUDO_PAYLOAD_REPLY_ID_START => prepare_sudo_payload(deps, env, msg),
         _ => Err(StdError::generic_err(format!(
             "unsupported reply message id {}",
             msg.id
@@ -171,5 +420,11 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
     deps.api.debug("WASMDEBUG: migrate");
-    Ok(Response::default())
+    let errors = reindex_errors(deps.storage)?;
+    let custody = migrate_custody_funds(deps.storage)?;
+    let applications = migrate_applications(deps.storage)?;
+    Ok(Response::default()
+        .add_attribute("reindexed_errors", errors.to_string())
+        .add_attribute("migrated_custody_funds", custody.to_string())
+        .add_attribute("migrated_applications", applications.to_string()))
 }
@@ -1,39 +1,142 @@
-use cosmwasm_std::{Storage, Addr, MessageInfo, Uint128, Response, Order, Env, StdResult, Decimal, StdError, CosmosMsg, BankMsg, coins};
-use neutron_sdk::{NeutronError, bindings::msg::NeutronMsg, interchain_txs::helpers::get_port_id};
+use cosmos_sdk_proto::cosmos::{bank::v1beta1::MsgSend, base::v1beta1::Coin as ProtoCoin};
+use cosmwasm_std::{Storage, Addr, MessageInfo, Uint128, Response, Event, Order, Env, StdResult, Decimal, StdError, CosmosMsg, BankMsg, coins, coin, Coin, DepsMut, SubMsg, Binary};
+use cw_storage_plus::Bound;
+use cw_utils::{may_pay, must_pay, nonpayable, Expiration};
+use neutron_sdk::{NeutronError, bindings::{msg::{NeutronMsg, IbcFee, MsgExecuteContract}, query::NeutronQuery, types::ProtobufAny}, interchain_txs::helpers::get_port_id, query::min_ibc_fee::query_min_ibc_fee, interchain_queries::v045::types::COSMOS_SDK_TRANSFER_MSG_URL};
+use prost::Message;
 
-use crate::{storage::{PROPOSALS, PROPOSAL_INDEX, Application, PROPOSAL_FUNDING, Proposal, CUSTODY_FUNDS, APPLICATIONS, CustodyFunds, APPLICATION_FUNDING, INTERCHAIN_ACCOUNTS}, utils::{valid_application, shareholders}, msg::{NeutronResponse, ApplicationSubmission}, query::{get_application_funds, get_proposal_funds_token, get_proposal_funds, get_address_funds}};
+use crate::{storage::{custody_key, save_participant_roles, PARTICIPANT_APPLICATIONS, ParticipantRole, PROPOSALS, PROPOSAL_INDEX, Application, PROPOSAL_FUNDING, Proposal, CUSTODY_FUNDS, APPLICATIONS, CustodyFunds, APPLICATION_FUNDING, INTERCHAIN_ACCOUNTS, QUARANTINED_DEPOSITS, QuarantineReason, CONFIG, ConflictRules, BondRules, GoodFee, TOTAL_SHARE_BPS, APPLICATION_BACKERS, APPLICATION_CONTRIBUTIONS, REPUTATION, ReputationStats, Deliverable, ExtensionRequest, EXTENSION_REQUESTS, EXTENSION_VOTES, EXTENSION_VOTING_PERIOD, MAX_SELECTION_APPLICATIONS, UPDATES, ProgressUpdate, UpdateComment, MAX_UPDATE_TEXT_LENGTH, ReputationRequirement, AUDITORS, AuditorProfile, DISPUTES, DISPUTE_INDEX, AUDITOR_DISPUTES, Dispute, DisputeStatus, ProposalStatus, Selection, Bounty, PROPOSAL_QUERIES, QUERY_TO_PROPOSAL, BALANCE_QUERY_IDS, QueryKind, PROPOSAL_ICAS, CONNECTION_CHAINS, InterchainAccount, IcaStatus, QUERY_CREDITS, QueryDeposit, REGISTRATION_QUERY_CREDITS, REGISTRATION_FEES, SudoPayload, FeePayment, IcaOperation, save_reply_payload, DEFAULT_TIMEOUT_SECONDS, FEE_RESERVE, OUTBOX, RETRY_BATCH_LIMIT, RETRY_SCHEDULE_NAME, PendingOperation, next_operation_id, ERRORS_QUEUE}, utils::{add_custody, lock_pool, is_remote_recipient, local_address, crossfund_event, funded_event, validate_application, validate_shareholders, shareholders, payout_legs, min_ntrn_ibc_fee, ica_id_from_port, ica_id, fee_denom_amount, total_ibc_fee, FEE_DENOM}, msg::{NeutronResponse, ApplicationSubmission, ExecuteMsg}, query::{get_application_funds, get_proposal_funds_token, get_proposal_funds, get_address_funds, get_application_locked_funds}};
 
 
 pub fn submit_proposal(
     store: &mut dyn Storage,
+    sender: Addr,
     title: String,
-    description: String
+    description: String,
+    tags: Vec<String>,
 ) -> NeutronResponse {
+    let mut tags : Vec<String> = tags
+        .iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+
     let index = PROPOSAL_INDEX.load(store).unwrap_or(0);
-    PROPOSALS.save(store, index.clone(), &Proposal { title, description, funding: Vec::new() })?;
+
+    let event = crossfund_event("proposal-created")
+        .add_attribute("proposal_id", index.to_string())
+        .add_attribute("creator", sender.as_str())
+        .add_attribute("title", title.as_str())
+        .add_attribute("tags", tags.join(","));
+
+    PROPOSALS.save(store, index, &Proposal { 
+        title, 
+        description, 
+        funding: Vec::new(),
+        creator: Some(sender),
+        status: ProposalStatus::Open,
+        tags,
+        require_registered_auditors: false,
+        min_reputation: None,
+        selection: None,
+        bounty: None,
+    })?;
     PROPOSAL_INDEX.save(store, &(index+1))?;
-    Ok(Response::default())
+    Ok(Response::default().add_event(event))
 }
 
 
 pub fn submit_application(
-    store: &mut dyn Storage,
+    deps: DepsMut<NeutronQuery>,
     env: Env,
     sender: Addr,
     proposal_id: u64,
-    application: ApplicationSubmission
+    mut application: ApplicationSubmission
 ) -> NeutronResponse {
-    if !valid_application(&application, &env.block) {
-        return Err(NeutronError::InvalidApplication);
+    validate_application(deps.api, &mut application, &env.block)?;
+
+    let store = deps.storage;
+
+    let config = CONFIG.load(store)?;
+    let proposal = PROPOSALS.load(store, proposal_id)?;
+
+    if proposal.selection.as_ref().is_some_and(|s| s.selected.is_some() || s.ends.is_expired(&env.block)) {
+        return Err(NeutronError::SelectionClosed{});
+    }
+
+    // every application of a selection is ranked at once when it's finalized
+    if proposal.selection.is_some() && !APPLICATIONS.has(store, (proposal_id, sender.clone())) {
+        let submitted = APPLICATIONS
+            .prefix(proposal_id)
+            .keys(store, None, None, Order::Ascending)
+            .take(MAX_SELECTION_APPLICATIONS)
+            .count();
+        if submitted == MAX_SELECTION_APPLICATIONS {
+            return Err(NeutronError::InvalidSelection { reason: format!("the selection is limited to {} applications", MAX_SELECTION_APPLICATIONS) });
+        }
+    }
+
+    if proposal.bounty.is_some() {
+        return Err(NeutronError::InvalidBounty { reason: "submissions to a bounty are made by delivering the work".to_string() });
+    }
+
+    for auditor in application.auditors.iter() {
+        check_auditor_conflicts(store, &config.conflict_rules, proposal_id, &sender, &application.applicants, &auditor.recipient)?;
+        if proposal.require_registered_auditors {
+            check_registered_auditor(store, &config.bond_rules, &auditor.recipient)?;
+        }
+    }
+
+    if let Some(requirement) = &proposal.min_reputation {
+        for applicant in application.applicants.iter() {
+            let stats = REPUTATION.may_load(store, &applicant.recipient)?.unwrap_or_default();
+            if !requirement.is_met(&stats) {
+                return Err(NeutronError::ReputationTooLow { applicant: applicant.recipient.to_string() });
+            }
+        }
+    }
+
+    // a resubmitted application may have different participants
+    let mut previous_participants = vec![];
+    if let Some(previous) = APPLICATIONS.may_load(store, (proposal_id, sender.clone()))? {
+        // the deliverables are the record of the work, resubmitting would drop them
+        if !previous.deliverables.is_empty() {
+            return Err(NeutronError::AlreadyDelivered{});
+        }
+        previous_participants = participants(&previous);
+        for participant in previous_participants.iter() {
+            PARTICIPANT_APPLICATIONS.remove(store, (participant, proposal_id, &sender));
+        }
     }
-    APPLICATIONS.save(store, (proposal_id, sender), &Application { 
+
+    let application = Application { 
         applicants: application.applicants, 
         auditors: application.auditors, 
         deliver_by: application.deliver_by, 
         accepted: false, 
         verifications: vec![],
-    })?;
-    Ok(Response::default())
+        expired: false,
+        deliverables: vec![],
+        budget: application.budget,
+    };
+
+    let new_participants : Vec<Addr> = participants(&application)
+        .into_iter()
+        .filter(|p| !previous_participants.contains(p))
+        .collect();
+    record_reputation(store, &new_participants, |stats| stats.submitted += 1)?;
+    save_participant_roles(store, proposal_id, &sender, &application)?;
+
+    let event = crossfund_event("application-submitted")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("application", sender.as_str())
+        .add_attribute("applicants", application.applicants.len().to_string())
+        .add_attribute("auditors", application.auditors.len().to_string());
+
+    APPLICATIONS.save(store, (proposal_id, sender), &application)?;
+    Ok(Response::default().add_event(event))
 }
 
 
@@ -41,10 +144,13 @@ pub fn fund_proposal_native(
     store: &mut dyn Storage,
     info: MessageInfo,
     proposal_id: u64,
-    auto_agree: Option<bool>
+    auto_agree: Option<bool>,
+    require_registered_auditors: Option<bool>,
 ) -> NeutronResponse {
 
     let sender = info.sender;
+    let mut events = Vec::with_capacity(info.funds.len());
+    let bounty_open = PROPOSALS.load(store, proposal_id)?.bounty_open();
 
     for coin in info.funds {
         if coin.amount == Uint128::zero() {
@@ -57,15 +163,23 @@ pub fn fund_proposal_native(
 
         PROPOSAL_FUNDING.save(store, (proposal_id, coin.denom.as_str()), &funding)?;
 
-        CUSTODY_FUNDS.save(store, (&sender, coin.denom.as_str()), &CustodyFunds {
-            amount: coin.amount,
-            proposal_id,
-            locked: false,
-            remote: None
+        CUSTODY_FUNDS.update(store, custody_key(&sender, proposal_id, &coin.denom, None), |f| -> StdResult<_> {
+            let funds = add_custody(f, coin.amount, proposal_id, None);
+            Ok(if bounty_open { lock_pool(funds) } else { funds })
         })?;
+
+        events.push(funded_event(proposal_id, sender.as_str(), &coin.denom, coin.amount, auto_agree.unwrap_or(false))
+            .add_attribute("source", "native"));
+    }
+
+    // only funders can restrict the auditors and the restriction can't be lifted
+    if require_registered_auditors == Some(true) && !events.is_empty() {
+        let mut proposal = PROPOSALS.load(store, proposal_id)?;
+        proposal.require_registered_auditors = true;
+        PROPOSALS.save(store, proposal_id, &proposal)?;
     }
 
-    Ok(Response::default())
+    Ok(Response::default().add_events(events))
 }
 
 
@@ -77,36 +191,70 @@ pub fn approve_application(
     application_sender: Addr
 ) -> NeutronResponse {
 
+    let application = APPLICATIONS.may_load(store, (proposal_id, application_sender.clone()))?;
+
+    if application.as_ref().is_some_and(|a| a.expired) {
+        return Err(NeutronError::ApplicationExpired{});
+    }
+
+    let proposal = PROPOSALS.load(store, proposal_id)?;
+
+    if proposal.bounty.is_some() {
+        return Err(NeutronError::InvalidBounty { reason: "the whole funding goes to the first verified submission".to_string() });
+    }
+
+    // approvals are counted until the selection is finalized, afterwards only the winners can get more
+    if let Some(selection) = proposal.selection {
+        if selection.selected.is_some() {
+            check_selected(&selection, &application_sender)?;
+        }
+    }
+
+    // denoms outside of the budget stay with the funder
     let user_funds = CUSTODY_FUNDS
-        .prefix(sender)
+        .prefix((sender, proposal_id))
         .range(store, None, None, Order::Ascending)
-        .map(|f| f.unwrap())
-        .filter(|f| f.1.proposal_id == proposal_id && !f.1.locked)
-        .collect::<Vec<_>>()
+        .filter(|f| f.as_ref().map_or(true, |((denom, _), funds)| {
+            !funds.locked && application.as_ref().is_none_or(|a| a.claims(denom))
+        }))
+        .collect::<StdResult<Vec<_>>>()?
     ;
 
     if user_funds.is_empty() {
         return Err(NeutronError::CantVote{});
     }
 
-    // let application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
+    let mut events = Vec::with_capacity(user_funds.len());
 
-    for (key, value) in user_funds {
+    for ((key, account), value) in user_funds {
         let existing =  APPLICATION_FUNDING.load(store, 
             (proposal_id, application_sender.clone(), key.as_str()))
             .unwrap_or_default();
 
         APPLICATION_FUNDING.save(store, (proposal_id, application_sender.clone(), key.as_str()), &(existing + value.amount))?;
+        APPLICATION_BACKERS.save(store, (proposal_id, &application_sender, sender), &value.remote.is_some())?;
         
-        CUSTODY_FUNDS.save(store, (sender, key.as_str()), &CustodyFunds {
-            amount: value.amount,
-            proposal_id,
+        events.push(crossfund_event("application-approved")
+            .add_attribute("proposal_id", proposal_id.to_string())
+            .add_attribute("application", application_sender.as_str())
+            .add_attribute("funder", sender.as_str())
+            .add_attribute("denom", key.as_str())
+            .add_attribute("amount", value.amount.to_string()));
+
+        CUSTODY_FUNDS.save(store, (sender, proposal_id, (key.as_str(), account.as_str())), &CustodyFunds {
             locked: true,
-            remote: None
+            application: Some(application_sender.clone()),
+            excess: Uint128::zero(),
+            ..value
         })?;
     }
 
-    Ok(Response::default())
+    // the approval came after the work started, the budget is split again with the new funds
+    if let Some(application) = application.filter(|a| a.accepted) {
+        lock_budget(store, proposal_id, &application_sender, &application.budget)?;
+    }
+
+    Ok(Response::default().add_events(events))
 }
 
 
@@ -114,23 +262,213 @@ pub fn approve_application(
 pub fn register_ica(
     store: &mut dyn Storage,
     env: Env,
+    info: MessageInfo,
     connection_id: String,
     proposal_id: u64,
 ) -> NeutronResponse {
 
+    if !PROPOSALS.has(store, proposal_id) {
+        return Err(NeutronError::Std(StdError::not_found("Proposal")));
+    }
+
+    let ica_id = ica_id(proposal_id, &connection_id);
+    let key = get_port_id(env.contract.address.as_str(), &ica_id);
+
+    // registering again on the same port reopens a closed channel and restores the same remote address
+    let previous = INTERCHAIN_ACCOUNTS.may_load(store, key.clone())?;
+    if matches!(previous, Some(InterchainAccount { status: IcaStatus::Open, .. })) {
+        return Err(NeutronError::IcaAlreadyRegistered{});
+    }
+
+    // the queries of a reopened account are kept, so only a new account pays for them
+    let credits = if previous.is_none() { REGISTRATION_QUERY_CREDITS } else { 0 };
+
+    let config = CONFIG.load(store)?;
+    let paid = may_pay(&info, FEE_DENOM).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let required = config.registration_fee + config.query_deposit * Uint128::from(credits as u128);
+
+    if paid < required {
+        return Err(NeutronError::InsufficientFunds { required: coin(required.u128(), FEE_DENOM).to_string() });
+    }
+
+    let address = previous.and_then(|ica| ica.address);
+
     let register =
-        NeutronMsg::register_interchain_account(connection_id, proposal_id.clone().to_string());
-    
-    let key = get_port_id(env.contract.address.as_str(), &proposal_id.to_string());
-    // we are saving empty data here because we handle response of registering ICA in sudo_open_ack method
+        NeutronMsg::register_interchain_account(connection_id.clone(), ica_id);
     
-    INTERCHAIN_ACCOUNTS.save(store, key, &None)?;
-    Ok(Response::new()
+    // the account becomes open when we handle response of registering ICA in sudo_open_ack method
+    INTERCHAIN_ACCOUNTS.save(store, key.clone(), &InterchainAccount {
+        address,
+        connection_id: connection_id.clone(),
+        status: IcaStatus::Registering,
+    })?;
+    PROPOSAL_ICAS.save(store, (proposal_id, connection_id.as_str()), &key)?;
+
+    if !config.query_deposit.is_zero() && credits > 0 {
+        add_query_credits(store, &key, &info.sender, config.query_deposit, credits)?;
+    }
+
+    let collected = REGISTRATION_FEES.may_load(store)?.unwrap_or_default();
+    REGISTRATION_FEES.save(store, &(collected + config.registration_fee))?;
+
+    let mut response = Response::new()
         .add_message(register)
+        .add_event(crossfund_event("ica-registration")
+            .add_attribute("proposal_id", proposal_id.to_string())
+            .add_attribute("connection_id", connection_id)
+            .add_attribute("port_id", key)
+            .add_attribute("payer", info.sender.as_str())
+            .add_attribute("fee", coin(required.u128(), FEE_DENOM).to_string())
+        );
+
+    if paid > required {
+        response = response.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins((paid - required).u128(), FEE_DENOM),
+        });
+    }
+
+    Ok(response)
+}
+
+
+
+pub fn sponsor_queries(
+    store: &mut dyn Storage,
+    info: MessageInfo,
+    proposal_id: u64,
+    connection_id: String,
+) -> NeutronResponse {
+
+    let key = PROPOSAL_ICAS.load(store, (proposal_id, connection_id.as_str()))?;
+    let deposit = CONFIG.load(store)?.query_deposit;
+
+    if deposit.is_zero() {
+        return Err(NeutronError::Std(StdError::generic_err("Interchain queries don't require deposits")));
+    }
+
+    let paid = may_pay(&info, FEE_DENOM).map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    if paid.is_zero() || !(paid % deposit).is_zero() {
+        return Err(NeutronError::InsufficientFunds { required: coin(deposit.u128(), FEE_DENOM).to_string() });
+    }
+
+    let count = (paid / deposit).u128() as usize;
+    add_query_credits(store, &key, &info.sender, deposit, count)?;
+
+    Ok(Response::default().add_event(crossfund_event("queries-sponsored")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("port_id", key)
+        .add_attribute("sponsor", info.sender.as_str())
+        .add_attribute("credits", count.to_string())
+    ))
+}
+
+
+
+pub fn withdraw_registration_fees(
+    deps: DepsMut<NeutronQuery>,
+    sender: Addr,
+    recipient: Option<String>,
+) -> NeutronResponse {
+
+    if sender != CONFIG.load(deps.storage)?.admin {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => sender,
+    };
+
+    let collected = REGISTRATION_FEES.may_load(deps.storage)?.unwrap_or_default();
+    if collected.is_zero() {
+        return Err(NeutronError::NoFunds{});
+    }
+    REGISTRATION_FEES.save(deps.storage, &Uint128::zero())?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(collected.u128(), FEE_DENOM),
+        })
+        .add_event(crossfund_event("registration-fees-withdrawn")
+            .add_attribute("recipient", recipient.as_str())
+            .add_attribute("amount", coin(collected.u128(), FEE_DENOM).to_string())
+        )
     )
 }
 
 
+pub fn update_fees(
+    store: &mut dyn Storage,
+    sender: Addr,
+    registration_fee: Uint128,
+    query_deposit: Uint128,
+) -> NeutronResponse {
+
+    CONFIG.update(store, |mut config| {
+        if sender != config.admin {
+            return Err(NeutronError::NonAuthorized{});
+        }
+        config.registration_fee = registration_fee;
+        config.query_deposit = query_deposit;
+        Ok(config)
+    })?;
+
+    Ok(Response::default().add_event(crossfund_event("fees-updated")
+        .add_attribute("registration_fee", registration_fee.to_string())
+        .add_attribute("query_deposit", query_deposit.to_string())
+    ))
+}
+
+
+
+pub fn update_conflict_rules(
+    store: &mut dyn Storage,
+    sender: Addr,
+    rules: ConflictRules,
+) -> NeutronResponse {
+
+    if rules.max_auditor_funding_bps.is_some_and(|bps| u32::from(bps) > TOTAL_SHARE_BPS) {
+        return Err(NeutronError::Std(StdError::generic_err("Funding share can't exceed 10000 basis points")));
+    }
+
+    CONFIG.update(store, |mut config| {
+        if sender != config.admin {
+            return Err(NeutronError::NonAuthorized{});
+        }
+        config.conflict_rules = rules.clone();
+        Ok(config)
+    })?;
+
+    Ok(Response::default().add_event(crossfund_event("conflict-rules-updated")
+        .add_attribute("separate_auditors", rules.separate_auditors.to_string())
+        .add_attribute("max_auditor_funding_bps", rules.max_auditor_funding_bps.map(|bps| bps.to_string()).unwrap_or_else(|| "none".to_string()))
+    ))
+}
+
+
+
+pub fn set_connection_chain(
+    store: &mut dyn Storage,
+    sender: Addr,
+    connection_id: String,
+    chain_id: String,
+) -> NeutronResponse {
+
+    if sender != CONFIG.load(store)?.admin {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    CONNECTION_CHAINS.save(store, connection_id.as_str(), &chain_id)?;
+    Ok(Response::default().add_event(crossfund_event("connection-chain-set")
+        .add_attribute("connection_id", connection_id)
+        .add_attribute("chain_id", chain_id)
+    ))
+}
+
+
 
 pub fn accept_application(
     store: &mut dyn Storage,
@@ -141,16 +479,50 @@ pub fn accept_application(
 
     let mut application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
 
-    if sender != application_sender &&  application.applicants.iter().all(|a| a.recipient != &sender) {
+    if sender != application_sender &&  application.applicants.iter().all(|a| a.recipient != sender) {
         return Err(NeutronError::NonAuthorized{});
     }
 
+    if application.expired {
+        return Err(NeutronError::ApplicationExpired{});
+    }
+
+    // accepting again would agree and lock the funds of the auto-agree funders twice
+    if application.accepted {
+        return Err(NeutronError::AlreadyAccepted{});
+    }
+
+    let proposal = PROPOSALS.load(store, proposal_id)?;
+
+    if proposal.bounty.is_some() {
+        return Err(NeutronError::InvalidBounty { reason: "submissions to a bounty don't need to be accepted".to_string() });
+    }
+
+    if let Some(selection) = proposal.selection {
+        check_selected(&selection, &application_sender)?;
+    }
+
+    let agreed = check_for_auto_agree(store, proposal_id, application_sender.clone())?;
+
+    let locked = lock_budget(store, proposal_id, &application_sender, &application.budget)?;
+
+    if let Some((budget, _)) = application.budget.iter().zip(&locked).find(|(b, l)| l.amount < b.amount) {
+        return Err(NeutronError::BudgetNotCovered { budget: budget.to_string() });
+    }
+
+    record_reputation(store, &participants(&application), |stats| stats.accepted += 1)?;
+
     application.accepted = true;
     APPLICATIONS.save(store, (proposal_id, application_sender.clone()), &application)?;
 
-    check_for_auto_agree(store, proposal_id, application_sender)?;
+    let event = crossfund_event("application-accepted")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("application", application_sender.as_str())
+        .add_attribute("accepted_by", sender.as_str());
 
-    Ok(Response::default())
+    Ok(Response::default()
+        .add_event(event.add_attribute("auto_agreed", agreed.to_string()))
+    )
 }
 
 
@@ -168,154 +540,1994 @@ pub fn verify_application(
         return Err(NeutronError::Std(StdError::generic_err("Application is not accepted")));
     }
 
-    if application.auditors.iter().all(|a| a.recipient != &sender) {
+    if application.expired {
+        return Err(NeutronError::ApplicationExpired{});
+    }
+
+    if application.deliverables.is_empty() {
+        return Err(NeutronError::DeliverableMissing{});
+    }
+
+    if application.auditors.iter().all(|a| a.recipient != sender) {
         return Err(NeutronError::NonAuthorized{});
     }
 
-    if application.verifications.iter().any(|v| v == &sender) {
+    if application.verifications.contains(&sender) {
         return Err(NeutronError::AlreadyVerified{});
     }
 
+    let proposal = PROPOSALS.load(store, proposal_id)?;
+
+    if proposal.bounty.is_some() && proposal.status == ProposalStatus::Completed {
+        return Err(NeutronError::BountyClosed{});
+    }
+
+    // the rules, the funding or the bond could have changed since the submission
+    let config = CONFIG.load(store)?;
+    check_auditor_conflicts(store, &config.conflict_rules, proposal_id, &application_sender, &application.applicants, &sender)?;
+    if proposal.require_registered_auditors {
+        check_registered_auditor(store, &config.bond_rules, &sender)?;
+    }
+
     application.verifications.push(sender.clone());
     
     APPLICATIONS.save(store, (proposal_id, application_sender.clone()), &application)?;
 
-    if application.auditors.len() == application.verifications.len() {
-        reward_applicants(store, proposal_id, application_sender)?;
+    let complete = application.auditors.len() == application.verifications.len();
+
+    let mut response = Response::default().add_event(crossfund_event("application-verified")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("application", application_sender.as_str())
+        .add_attribute("auditor", sender.as_str())
+        .add_attribute("verifications", application.verifications.len().to_string())
+        .add_attribute("complete", complete.to_string())
+    );
+
+    if complete {
+        if proposal.bounty.is_some() {
+            response = response.add_event(close_bounty(store, proposal_id, &application_sender)?);
+        }
+        let payouts = reward_applicants(store, proposal_id, application_sender)?;
+        // work nobody paid for doesn't count towards the reputation
+        if !payouts.is_empty() {
+            record_reputation(store, &participants(&application), |stats| stats.delivered += 1)?;
+        }
+        response = response.add_events(payouts);
+
+        // a selection is only done once every winner delivered or missed the deadline
+        let settled = match &proposal.selection {
+            Some(Selection { selected: Some(selected), .. }) => selection_settled(store, proposal_id, selected)?,
+            _ => true,
+        };
+        if settled {
+            response = response
+                .add_messages(complete_proposal(store, proposal_id)?)
+                .add_event(crossfund_event("proposal-completed")
+                    .add_attribute("proposal_id", proposal_id.to_string()));
+        }
     }
 
-    Ok(Response::default())
+    Ok(response)
 }
 
 
 
-pub fn withdraw_funds(
+/// Registers the sender as an auditor or tops up the bond and updates the profile
+pub fn register_auditor(
     store: &mut dyn Storage,
-    sender: Addr,
+    info: MessageInfo,
+    profile_uri: String,
 ) -> NeutronResponse {
 
-    let funds = get_address_funds(store, &sender, true)?;
+    let paid = may_pay(&info, FEE_DENOM).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let rules = CONFIG.load(store)?.bond_rules;
 
-    if funds.is_empty() {
-        return Err(NeutronError::NoFunds{});
+    let mut profile = AUDITORS.may_load(store, &info.sender)?.unwrap_or(AuditorProfile {
+        profile_uri: String::new(),
+        bond: Uint128::zero(),
+        unbonding_until: None,
+    });
+
+    if profile.unbonding_until.is_some() {
+        return Err(NeutronError::AuditorUnbonding{});
     }
 
-    // TODO: 
-    let messages : Vec::<CosmosMsg<NeutronMsg>> = funds
-        .iter()
-        .map(|(token, fund)| send_back_msg( &sender, token, fund))
-        .collect();
+    profile.bond += paid;
+    profile.profile_uri = profile_uri;
+
+    if profile.bond.is_zero() || profile.bond < rules.min_bond {
+        return Err(NeutronError::BondTooLow { required: coin(rules.min_bond.max(Uint128::one()).u128(), FEE_DENOM).to_string() });
+    }
 
-    Ok(Response::default().add_messages(messages))
+    AUDITORS.save(store, &info.sender, &profile)?;
 
+    Ok(Response::default().add_event(crossfund_event("auditor-registered")
+        .add_attribute("auditor", info.sender.as_str())
+        .add_attribute("profile_uri", profile.profile_uri)
+        .add_attribute("bond", coin(profile.bond.u128(), FEE_DENOM).to_string())
+    ))
 }
 
 
-fn reward_applicants(
+
+/// Takes the auditor out of the registry, the bond can still be slashed until the unbonding period ends
+pub fn unbond_auditor(
     store: &mut dyn Storage,
-    proposal_id: u64,
-    application_sender: Addr,
-) -> StdResult<()> {
+    env: Env,
+    sender: Addr,
+) -> NeutronResponse {
 
-    let application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
-    
-    let funds : Vec<((Addr, String), CustodyFunds)> = CUSTODY_FUNDS
-        .range(store, None, None, Order::Ascending)
-        .filter(|f| {
-            let funds = &f.as_ref().unwrap().1;
-            funds.proposal_id == proposal_id && funds.locked
-        })
-        .map(|f| f.unwrap())
-        .collect();
+    let mut profile = AUDITORS.load(store, &sender)?;
 
-    let mut sums: Vec<(String, Uint128)> = Vec::with_capacity(10);
+    if profile.unbonding_until.is_some() {
+        return Err(NeutronError::AuditorUnbonding{});
+    }
 
-    for ((sender, token), fund) in &funds {
-        
-        CUSTODY_FUNDS.remove(store, (sender, token.as_str()));
+    let release_at = env.block.height + CONFIG.load(store)?.bond_rules.unbonding_period;
+    profile.unbonding_until = Some(release_at);
+    AUDITORS.save(store, &sender, &profile)?;
 
-        if let Some(index) = sums.iter().position(|(t, _)| t == token) {
-            // Add the fund amount to the existing sum
-            sums[index].1 += fund.amount;
-        } else {
-            // Add a new sum entry for the token
-            sums.push((token.clone(), fund.amount));
-        }
+    Ok(Response::default().add_event(crossfund_event("auditor-unbonding")
+        .add_attribute("auditor", sender.as_str())
+        .add_attribute("release_at", release_at.to_string())
+    ))
+}
+
+
+
+pub fn claim_auditor_bond(
+    store: &mut dyn Storage,
+    env: Env,
+    sender: Addr,
+) -> NeutronResponse {
+
+    let profile = AUDITORS.load(store, &sender)?;
+
+    match profile.unbonding_until {
+        Some(release_at) if release_at <= env.block.height => {},
+        Some(release_at) => return Err(NeutronError::BondLocked { release_at }),
+        None => return Err(NeutronError::Std(StdError::generic_err("Auditor must unbond first"))),
     }
 
-    shareholders(&application).iter().for_each(|gf| {
-        sums.iter().for_each(|(token, total)| {
-            let amount: Uint128 = *total * Decimal::percent(gf.percent_share.into());
-            CUSTODY_FUNDS.save(store, (&gf.recipient, token.as_str()), &CustodyFunds {
-                amount,
-                proposal_id,
-                remote: None,
-                locked: false
-            }).unwrap();
+    AUDITORS.remove(store, &sender);
+
+    let mut response = Response::default().add_event(crossfund_event("auditor-bond-claimed")
+        .add_attribute("auditor", sender.as_str())
+        .add_attribute("bond", coin(profile.bond.u128(), FEE_DENOM).to_string())
+    );
+
+    if !profile.bond.is_zero() {
+        response = response.add_message(BankMsg::Send {
+            to_address: sender.to_string(),
+            amount: coins(profile.bond.u128(), FEE_DENOM),
         });
-    });
+    }
 
-    Ok(())
+    Ok(response)
 }
 
 
-fn check_for_auto_agree(
+
+pub fn update_bond_rules(
     store: &mut dyn Storage,
-    proposal_id: u64,
-    application_sender: Addr,
-) -> StdResult<()> {
-    for (token, amount) in  get_application_funds(store, proposal_id, application_sender)? {
-        let total = get_proposal_funds_token(store, proposal_id.clone(), token.as_str())?;
-        let ratio = Decimal::from_ratio(amount, total);
-        if ratio > Decimal::percent(50) {
-            auto_agree(store, proposal_id)?;
-        }
+    sender: Addr,
+    rules: BondRules,
+) -> NeutronResponse {
+
+    if u32::from(rules.slash_bps) > TOTAL_SHARE_BPS {
+        return Err(NeutronError::Std(StdError::generic_err("Slashed share can't exceed 10000 basis points")));
     }
-    Ok(())
+
+    CONFIG.update(store, |mut config| {
+        if sender != config.admin {
+            return Err(NeutronError::NonAuthorized{});
+        }
+        config.bond_rules = rules.clone();
+        Ok(config)
+    })?;
+
+    Ok(Response::default().add_event(crossfund_event("bond-rules-updated")
+        .add_attribute("min_bond", rules.min_bond.to_string())
+        .add_attribute("unbonding_period", rules.unbonding_period.to_string())
+        .add_attribute("slash_bps", rules.slash_bps.to_string())
+    ))
 }
 
 
-fn auto_agree(
+
+/// Funders that backed an application can dispute it once an auditor verified it
+pub fn raise_dispute(
     store: &mut dyn Storage,
+    sender: Addr,
     proposal_id: u64,
-) -> StdResult<()> {
+    application_sender: Addr,
+    reason: String,
+) -> NeutronResponse {
 
-    let funds = get_proposal_funds(store, proposal_id, Some(true))?;
+    let application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
 
-    for (token, funding) in &funds {
-        
-        CUSTODY_FUNDS.update(store, (&funding.sender, token.as_str()), |f| {
-            match f {
-                Some(mut f) => {
-                    f.locked = true;
-                    Ok(f)
-                },
-                None => Err(StdError::not_found("Custody funds"))
-            }
-        })?;
+    let verified = !application.verifications.is_empty() && application.verifications.len() == application.auditors.len();
 
-        APPLICATION_FUNDING.update(store, (proposal_id, funding.sender.clone(), token.as_str()), |f| {
-            match f {
-                Some(f) => Ok(f + funding.amount),
-                None => Err(StdError::not_found("Application funds"))
-            }
-        })?;
+    if !verified || !APPLICATION_BACKERS.has(store, (proposal_id, &application_sender, &sender)) {
+        return Err(NeutronError::CantDispute{});
     }
 
-    Ok(())
+    // an auditor can only be slashed once for an application
+    for auditor in application.verifications.iter() {
+        if let Some(id) = AUDITOR_DISPUTES.may_load(store, (proposal_id, &application_sender, auditor))? {
+            return Err(NeutronError::DisputeExists { id });
+        }
+    }
+
+    let id = DISPUTE_INDEX.may_load(store)?.unwrap_or_default();
+    DISPUTE_INDEX.save(store, &(id + 1))?;
+
+    for auditor in application.verifications.iter() {
+        AUDITOR_DISPUTES.save(store, (proposal_id, &application_sender, auditor), &id)?;
+    }
+
+    DISPUTES.save(store, id, &Dispute {
+        proposal_id,
+        application_sender: application_sender.clone(),
+        raised_by: sender.clone(),
+        reason,
+        status: DisputeStatus::Open,
+        slashed: Uint128::zero(),
+    })?;
+
+    Ok(Response::default().add_event(crossfund_event("dispute-raised")
+        .add_attribute("dispute_id", id.to_string())
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("application", application_sender.as_str())
+        .add_attribute("raised_by", sender.as_str())
+    ))
 }
 
 
-fn send_back_msg(
-    sender: &Addr,
-    token: &String,
-    fund: &CustodyFunds,
-) -> CosmosMsg<NeutronMsg> {
 
-    let send = BankMsg::Send {
-        to_address: sender.clone().to_string(),
-        amount: coins(fund.amount.u128(), token),
-    };
+/// An upheld dispute slashes the bonds of the registered auditors that verified the application.
+/// The slashed bonds are split between the funders pro-rata to what they paid out to the application,
+/// every denom weighs the same. Remote funders are credited in custody, the rounding dust goes to the fee reserve
+pub fn resolve_dispute(
+    store: &mut dyn Storage,
+    sender: Addr,
+    id: u64,
+    upheld: bool,
+) -> NeutronResponse {
+
+    let config = CONFIG.load(store)?;
 
-    send.into()
+    if sender != config.admin {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    let mut dispute = DISPUTES.load(store, id)?;
+
+    if dispute.status != DisputeStatus::Open {
+        return Err(NeutronError::DisputeNotOpen{});
+    }
+
+    let mut response = Response::default();
+    let application = APPLICATIONS.load(store, (dispute.proposal_id, dispute.application_sender.clone()))?;
+
+    if upheld {
+        record_reputation(store, &participants(&application), |stats| stats.disputed += 1)?;
+
+        for auditor in application.verifications.iter() {
+            let mut profile = match AUDITORS.may_load(store, auditor)? {
+                Some(profile) => profile,
+                None => continue,
+            };
+            let slashed = profile.bond.multiply_ratio(config.bond_rules.slash_bps, TOTAL_SHARE_BPS);
+            profile.bond -= slashed;
+            AUDITORS.save(store, auditor, &profile)?;
+            dispute.slashed += slashed;
+
+            response = response.add_event(crossfund_event("auditor-slashed")
+                .add_attribute("dispute_id", id.to_string())
+                .add_attribute("auditor", auditor.as_str())
+                .add_attribute("amount", coin(slashed.u128(), FEE_DENOM).to_string()));
+        }
+
+        let mut rest = dispute.slashed;
+
+        for (funder, share) in slash_shares(store, &dispute)? {
+            if share.is_zero() {
+                continue;
+            }
+            rest -= share;
+            let remote = APPLICATION_BACKERS
+                .may_load(store, (dispute.proposal_id, &dispute.application_sender, &funder))?
+                .unwrap_or_default();
+            if remote {
+                CUSTODY_FUNDS.update(store, custody_key(&funder, dispute.proposal_id, FEE_DENOM, None), |f| -> StdResult<_> {
+                    Ok(add_custody(f, share, dispute.proposal_id, None))
+                })?;
+            } else {
+                response = response.add_message(BankMsg::Send {
+                    to_address: funder.to_string(),
+                    amount: coins(share.u128(), FEE_DENOM),
+                });
+            }
+            response = response.add_event(crossfund_event("slash-distributed")
+                .add_attribute("dispute_id", id.to_string())
+                .add_attribute("funder", funder.as_str())
+                .add_attribute("amount", coin(share.u128(), FEE_DENOM).to_string())
+                .add_attribute("kind", if remote { "remote" } else { "native" }));
+        }
+
+        if !rest.is_zero() {
+            let reserve = FEE_RESERVE.may_load(store)?.unwrap_or_default();
+            FEE_RESERVE.save(store, &(reserve + rest))?;
+        }
+    } else {
+        // the verifications can be disputed again
+        for auditor in application.verifications.iter() {
+            AUDITOR_DISPUTES.remove(store, (dispute.proposal_id, &dispute.application_sender, auditor));
+        }
+    }
+
+    dispute.status = if upheld { DisputeStatus::Upheld } else { DisputeStatus::Rejected };
+    DISPUTES.save(store, id, &dispute)?;
+
+    Ok(response.add_event(crossfund_event("dispute-resolved")
+        .add_attribute("dispute_id", id.to_string())
+        .add_attribute("upheld", upheld.to_string())
+        .add_attribute("slashed", coin(dispute.slashed.u128(), FEE_DENOM).to_string())
+    ))
+}
+
+
+
+/// Shares of the slashed bonds per funder, every denom paid out to the application weighs the same
+/// and within a denom the funders are weighted by their contribution
+fn slash_shares(
+    store: &dyn Storage,
+    dispute: &Dispute,
+) -> StdResult<Vec<(Addr, Uint128)>> {
+
+    let contributions = APPLICATION_CONTRIBUTIONS
+        .prefix((dispute.proposal_id, &dispute.application_sender))
+        .range(store, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Addr, Vec<Coin>)>>>()?;
+
+    let mut totals: Vec<Coin> = vec![];
+    for c in contributions.iter().flat_map(|(_, coins)| coins) {
+        match totals.iter_mut().find(|t| t.denom == c.denom) {
+            Some(total) => total.amount += c.amount,
+            None => totals.push(c.clone()),
+        }
+    }
+
+    if totals.is_empty() {
+        return Ok(vec![]);
+    }
+    let denoms = Decimal::from_ratio(totals.len() as u128, 1u128);
+
+    Ok(contributions
+        .into_iter()
+        .map(|(funder, coins)| {
+            let weight = coins
+                .iter()
+                .filter_map(|c| totals.iter().find(|t| t.denom == c.denom).map(|t| Decimal::from_ratio(c.amount, t.amount)))
+                .fold(Decimal::zero(), |sum, w| sum + w);
+            (funder, dispute.slashed * (weight / denoms))
+        })
+        .collect())
+}
+
+
+
+/// Records the work done for an accepted application, auditors can verify it afterwards.
+/// Without `application_sender` the application is the one the sender is an applicant of
+pub fn submit_deliverable(
+    store: &mut dyn Storage,
+    env: Env,
+    sender: Addr,
+    proposal_id: u64,
+    application_sender: Option<Addr>,
+    uri: String,
+    content_hash: String,
+) -> NeutronResponse {
+
+    let proposal = PROPOSALS.load(store, proposal_id)?;
+
+    // hunters of a bounty deliver right away, the first delivery opens their submission
+    if let Some(bounty) = &proposal.bounty {
+        if proposal.status == ProposalStatus::Completed {
+            return Err(NeutronError::BountyClosed{});
+        }
+        if application_sender.is_none() && !APPLICATIONS.has(store, (proposal_id, sender.clone())) {
+            open_bounty_submission(store, &sender, proposal_id, &proposal, bounty)?;
+        }
+    }
+
+    let (application_sender, mut application) = applicant_application(store, &sender, proposal_id, application_sender)?;
+
+    if uri.trim().is_empty() {
+        return Err(NeutronError::InvalidDeliverable { reason: "empty uri".to_string() });
+    }
+
+    if content_hash.len() != 64 || !content_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(NeutronError::InvalidDeliverable { reason: "content hash must be a hex encoded SHA-256".to_string() });
+    }
+
+    if !application.verifications.is_empty() && application.verifications.len() == application.auditors.len() {
+        return Err(NeutronError::InvalidDeliverable { reason: "application is already verified".to_string() });
+    }
+
+    let deliverable = Deliverable {
+        uri,
+        content_hash: content_hash.to_lowercase(),
+        submitted_by: sender,
+        submitted_at: env.block.time,
+    };
+
+    let event = crossfund_event("deliverable-submitted")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("application", application_sender.as_str())
+        .add_attribute("submitted_by", deliverable.submitted_by.as_str())
+        .add_attribute("uri", deliverable.uri.as_str())
+        .add_attribute("content_hash", deliverable.content_hash.as_str())
+        .add_attribute("version", (application.deliverables.len() + 1).to_string())
+        .add_attribute("verifications_reset", application.verifications.len().to_string());
+
+    // verifications are for the previous version, the auditors have to check the new one
+    application.verifications.clear();
+    application.deliverables.push(deliverable);
+    APPLICATIONS.save(store, (proposal_id, application_sender), &application)?;
+
+    Ok(Response::default().add_event(event))
+}
+
+
+
+/// Asks the funders of an application to move its deadline
+pub fn request_extension(
+    store: &mut dyn Storage,
+    env: Env,
+    sender: Addr,
+    proposal_id: u64,
+    application_sender: Option<Addr>,
+    deliver_by: Expiration,
+    reason: String,
+) -> NeutronResponse {
+
+    let (application_sender, application) = applicant_application(store, &sender, proposal_id, application_sender)?;
+
+    if application.deliver_by.is_expired(&env.block) {
+        return Err(NeutronError::ApplicationExpired{});
+    }
+
+    if deliver_by.partial_cmp(&application.deliver_by) != Some(std::cmp::Ordering::Greater) || deliver_by == (Expiration::Never {}) {
+        return Err(NeutronError::InvalidExtension { reason: "new deadline must be later than the current one and of the same kind".to_string() });
+    }
+
+    // a lapsed request can be replaced
+    if let Some(open) = EXTENSION_REQUESTS.may_load(store, (proposal_id, &application_sender))? {
+        if open.voting_ends >= env.block.height {
+            return Err(NeutronError::ExtensionPending{});
+        }
+        clear_extension(store, proposal_id, &application_sender)?;
+    }
+
+    let voting_ends = env.block.height + EXTENSION_VOTING_PERIOD;
+
+    EXTENSION_REQUESTS.save(store, (proposal_id, &application_sender), &ExtensionRequest {
+        deliver_by,
+        reason,
+        requested_by: sender.clone(),
+        voting_ends,
+        votes_for: Decimal::zero(),
+        votes_against: Decimal::zero(),
+    })?;
+
+    Ok(Response::default().add_event(crossfund_event("extension-requested")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("application", application_sender.as_str())
+        .add_attribute("requested_by", sender.as_str())
+        .add_attribute("deliver_by", deliver_by.to_string())
+        .add_attribute("voting_ends", voting_ends.to_string())
+    ))
+}
+
+
+
+/// Funders with funds locked for the application vote on its extension request.
+/// The request is approved as soon as more than half of the weight is for it and rejected once half is against it
+pub fn vote_extension(
+    store: &mut dyn Storage,
+    env: Env,
+    sender: Addr,
+    proposal_id: u64,
+    application_sender: Addr,
+    approve: bool,
+) -> NeutronResponse {
+
+    let mut request = EXTENSION_REQUESTS
+        .may_load(store, (proposal_id, &application_sender))?
+        .filter(|r| r.voting_ends >= env.block.height)
+        .ok_or(NeutronError::NoExtensionRequest{})?;
+
+    let mut application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
+
+    if application.expired || application.deliver_by.is_expired(&env.block) {
+        return Err(NeutronError::ApplicationExpired{});
+    }
+
+    let funds = get_application_locked_funds(store, proposal_id, &application_sender)?;
+
+    if funds.iter().all(|((funder, _), _)| *funder != sender) {
+        return Err(NeutronError::CantVote{});
+    }
+
+    EXTENSION_VOTES.save(store, (proposal_id, &application_sender, &sender), &approve)?;
+
+    // every denom counts equally, within a denom the votes are weighted by the locked amount
+    let mut totals : Vec<(String, Uint128)> = vec![];
+    for ((_, denom), f) in funds.iter() {
+        match totals.iter_mut().find(|(d, _)| d == denom) {
+            Some((_, total)) => *total += f.locked_amount(),
+            None => totals.push((denom.clone(), f.locked_amount())),
+        }
+    }
+
+    request.votes_for = Decimal::zero();
+    request.votes_against = Decimal::zero();
+
+    for ((funder, denom), f) in funds.iter() {
+        let vote = EXTENSION_VOTES.may_load(store, (proposal_id, &application_sender, funder))?;
+        let total = totals.iter().find(|(d, _)| d == denom).map(|(_, t)| *t).unwrap_or_default();
+        let weight = Decimal::from_ratio(f.locked_amount(), total * Uint128::from(totals.len() as u128));
+        match vote {
+            Some(true) => request.votes_for += weight,
+            Some(false) => request.votes_against += weight,
+            None => {},
+        }
+    }
+
+    let mut event = crossfund_event("extension-voted")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("application", application_sender.as_str())
+        .add_attribute("voter", sender.as_str())
+        .add_attribute("approve", approve.to_string())
+        .add_attribute("votes_for", request.votes_for.to_string())
+        .add_attribute("votes_against", request.votes_against.to_string());
+
+    if request.votes_for > Decimal::percent(50) {
+        application.deliver_by = request.deliver_by;
+        APPLICATIONS.save(store, (proposal_id, application_sender.clone()), &application)?;
+        clear_extension(store, proposal_id, &application_sender)?;
+        event = event.add_attribute("outcome", "approved");
+    } else if request.votes_against >= Decimal::percent(50) {
+        clear_extension(store, proposal_id, &application_sender)?;
+        event = event.add_attribute("outcome", "rejected");
+    } else {
+        EXTENSION_REQUESTS.save(store, (proposal_id, &application_sender), &request)?;
+    }
+
+    Ok(Response::default().add_event(event))
+}
+
+
+
+/// Appends a progress report to an accepted application for its funders to follow.
+/// Without `application_sender` the application is the one the sender is an applicant of
+pub fn post_update(
+    store: &mut dyn Storage,
+    env: Env,
+    sender: Addr,
+    proposal_id: u64,
+    application_sender: Option<Addr>,
+    uri: String,
+    summary: String,
+) -> NeutronResponse {
+
+    let (application_sender, _) = applicant_application(store, &sender, proposal_id, application_sender)?;
+
+    if uri.trim().is_empty() {
+        return Err(NeutronError::InvalidUpdate { reason: "empty uri".to_string() });
+    }
+    check_update_text(&summary)?;
+
+    let update_id = UPDATES
+        .prefix((proposal_id, &application_sender))
+        .keys(store, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .map_or(0, |id| id + 1);
+
+    let update = ProgressUpdate {
+        uri,
+        summary,
+        posted_by: sender,
+        posted_at: env.block.time,
+        comments: vec![],
+    };
+    UPDATES.save(store, (proposal_id, &application_sender, update_id), &update)?;
+
+    Ok(Response::default().add_event(
+        crossfund_event("update-posted")
+            .add_attribute("proposal_id", proposal_id.to_string())
+            .add_attribute("application", application_sender.as_str())
+            .add_attribute("update_id", update_id.to_string())
+            .add_attribute("posted_by", update.posted_by.as_str())
+            .add_attribute("uri", update.uri)
+    ))
+}
+
+
+/// Review comment of an auditor of the application on one of its updates
+pub fn comment_update(
+    store: &mut dyn Storage,
+    env: Env,
+    sender: Addr,
+    proposal_id: u64,
+    application_sender: Addr,
+    update_id: u64,
+    comment: String,
+) -> NeutronResponse {
+
+    let application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
+
+    if application.auditors.iter().all(|a| a.recipient != sender) {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    check_update_text(&comment)?;
+
+    let mut update = UPDATES.load(store, (proposal_id, &application_sender, update_id))?;
+
+    update.comments.push(UpdateComment {
+        auditor: sender.clone(),
+        comment,
+        posted_at: env.block.time,
+    });
+    UPDATES.save(store, (proposal_id, &application_sender, update_id), &update)?;
+
+    Ok(Response::default().add_event(
+        crossfund_event("update-commented")
+            .add_attribute("proposal_id", proposal_id.to_string())
+            .add_attribute("application", application_sender.as_str())
+            .add_attribute("update_id", update_id.to_string())
+            .add_attribute("auditor", sender.as_str())
+    ))
+}
+
+
+/// Marks an application that missed its deadline as expired and unlocks the funds approved for it
+pub fn expire_application(
+    store: &mut dyn Storage,
+    env: Env,
+    proposal_id: u64,
+    application_sender: Addr,
+) -> NeutronResponse {
+
+    let mut application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
+
+    if application.expired 
+        || !application.deliver_by.is_expired(&env.block) 
+        || application.verifications.len() == application.auditors.len() {
+        return Err(NeutronError::NotExpirable{});
+    }
+
+    application.expired = true;
+    APPLICATIONS.save(store, (proposal_id, application_sender.clone()), &application)?;
+
+    // only the teams that committed to the work are accountable for the deadline
+    if application.accepted {
+        record_reputation(store, &participants(&application), |stats| stats.expired += 1)?;
+    }
+
+    let funds = get_application_locked_funds(store, proposal_id, &application_sender)?;
+
+    for ((funder, denom), funds) in funds.iter() {
+        CUSTODY_FUNDS.save(store, custody_key(funder, proposal_id, denom, funds.remote.as_deref()), &CustodyFunds {
+            locked: false,
+            application: None,
+            excess: Uint128::zero(),
+            ..funds.clone()
+        })?;
+
+        let key = (proposal_id, application_sender.clone(), denom.as_str());
+        let approved = APPLICATION_FUNDING.may_load(store, key.clone())?.unwrap_or_default().saturating_sub(funds.amount);
+        if approved.is_zero() {
+            APPLICATION_FUNDING.remove(store, key);
+        } else {
+            APPLICATION_FUNDING.save(store, key, &approved)?;
+        }
+    }
+
+    let mut response = Response::default().add_event(crossfund_event("application-expired")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("application", application_sender.as_str())
+        .add_attribute("accepted", application.accepted.to_string())
+        .add_attribute("unlocked", funds.len().to_string())
+    );
+
+    // the last winner of a selection to miss the deadline completes the proposal
+    let proposal = PROPOSALS.load(store, proposal_id)?;
+    if let Some(Selection { selected: Some(selected), .. }) = &proposal.selection {
+        if proposal.status != ProposalStatus::Completed
+            && selected.contains(&application_sender)
+            && selection_settled(store, proposal_id, selected)? {
+            response = response
+                .add_messages(complete_proposal(store, proposal_id)?)
+                .add_event(crossfund_event("proposal-completed")
+                    .add_attribute("proposal_id", proposal_id.to_string()));
+        }
+    }
+
+    Ok(response)
+}
+
+
+
+pub fn set_min_reputation(
+    store: &mut dyn Storage,
+    sender: Addr,
+    proposal_id: u64,
+    requirement: Option<ReputationRequirement>,
+) -> NeutronResponse {
+
+    let mut proposal = PROPOSALS.load(store, proposal_id)?;
+
+    if !proposal.is_creator(&sender) {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    proposal.min_reputation = requirement.clone();
+    PROPOSALS.save(store, proposal_id, &proposal)?;
+
+    let mut event = crossfund_event("min-reputation-set")
+        .add_attribute("proposal_id", proposal_id.to_string());
+
+    if let Some(requirement) = requirement {
+        event = event
+            .add_attribute("min_delivered", requirement.min_delivered.to_string())
+            .add_attribute("max_expired", requirement.max_expired.map(|m| m.to_string()).unwrap_or_else(|| "none".to_string()))
+            .add_attribute("max_disputed", requirement.max_disputed.map(|m| m.to_string()).unwrap_or_else(|| "none".to_string()));
+    }
+
+    Ok(Response::default().add_event(event))
+}
+
+
+
+/// Turns the proposal into a competitive bidding, applications are collected until `ends`
+/// and then up to `winners` of them are picked by the approvals of the funders
+pub fn open_selection(
+    store: &mut dyn Storage,
+    env: Env,
+    sender: Addr,
+    proposal_id: u64,
+    ends: Expiration,
+    winners: u32,
+) -> NeutronResponse {
+
+    let mut proposal = PROPOSALS.load(store, proposal_id)?;
+
+    if !proposal.is_creator(&sender) {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    if proposal.selection.as_ref().is_some_and(|s| s.selected.is_some()) {
+        return Err(NeutronError::SelectionClosed{});
+    }
+
+    if winners == 0 {
+        return Err(NeutronError::InvalidSelection { reason: "at least one application must be selected".to_string() });
+    }
+
+    if proposal.bounty.is_some() {
+        return Err(NeutronError::InvalidSelection { reason: "bounties are won by the first verified submission".to_string() });
+    }
+
+    if ends == (Expiration::Never {}) || ends.is_expired(&env.block) {
+        return Err(NeutronError::InvalidSelection { reason: "end of the selection must be in the future".to_string() });
+    }
+
+    let applications = APPLICATIONS
+        .prefix(proposal_id)
+        .range(store, None, None, Order::Ascending)
+        .take(MAX_SELECTION_APPLICATIONS + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    if applications.len() > MAX_SELECTION_APPLICATIONS {
+        return Err(NeutronError::InvalidSelection { reason: format!("the selection is limited to {} applications", MAX_SELECTION_APPLICATIONS) });
+    }
+
+    if applications.iter().any(|(_, a)| a.accepted) {
+        return Err(NeutronError::InvalidSelection { reason: "work on the proposal has already started".to_string() });
+    }
+
+    proposal.selection = Some(Selection { ends, winners, selected: None });
+    PROPOSALS.save(store, proposal_id, &proposal)?;
+
+    Ok(Response::default().add_event(crossfund_event("selection-opened")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("ends", ends.to_string())
+        .add_attribute("winners", winners.to_string())
+    ))
+}
+
+
+/// Turns the proposal into a bounty paid to the first submission verified by the given auditors
+pub fn open_bounty(
+    deps: DepsMut<NeutronQuery>,
+    sender: Addr,
+    proposal_id: u64,
+    mut auditors: Vec<GoodFee>,
+) -> NeutronResponse {
+
+    let store = deps.storage;
+    let mut proposal = PROPOSALS.load(store, proposal_id)?;
+
+    if !proposal.is_creator(&sender) {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    if proposal.selection.is_some() {
+        return Err(NeutronError::InvalidBounty { reason: "proposal has a selection phase".to_string() });
+    }
+
+    if !APPLICATIONS.prefix(proposal_id).is_empty(store) {
+        return Err(NeutronError::InvalidBounty { reason: "proposal already has applications".to_string() });
+    }
+
+    if auditors.is_empty() || auditors.len() >= 100 {
+        return Err(NeutronError::InvalidBounty { reason: "bounty needs between 1 and 99 auditors".to_string() });
+    }
+
+    // the submitter gets the rest of the shares
+    let total = validate_shareholders(deps.api, &mut auditors)?;
+    if total >= TOTAL_SHARE_BPS {
+        return Err(NeutronError::InvalidShareTotal { total });
+    }
+
+    if proposal.require_registered_auditors {
+        let config = CONFIG.load(store)?;
+        for auditor in auditors.iter() {
+            check_registered_auditor(store, &config.bond_rules, &auditor.recipient)?;
+        }
+    }
+
+    let event = crossfund_event("bounty-opened")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("auditors", auditors.len().to_string())
+        .add_attribute("auditors_share_bps", total.to_string());
+
+    // the pool can't be withdrawn while hunters are working on it
+    let funds = CUSTODY_FUNDS
+        .idx
+        .proposal
+        .prefix(proposal_id)
+        .range(store, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((funder, _, (denom, account)), funds) in funds {
+        CUSTODY_FUNDS.save(store, (&funder, proposal_id, (denom.as_str(), account.as_str())), &lock_pool(funds))?;
+    }
+
+    proposal.bounty = Some(Bounty { auditors });
+    PROPOSALS.save(store, proposal_id, &proposal)?;
+
+    Ok(Response::default().add_event(event))
+}
+
+
+/// Picks the applications with the most approvals once the selection ended, anyone can trigger it.
+/// Every denom of the proposal counts equally and the budgets of the winners must fit in the funding.
+/// Funds approved for the other applications are unlocked
+pub fn select_applications(
+    store: &mut dyn Storage,
+    env: Env,
+    proposal_id: u64,
+) -> NeutronResponse {
+
+    let mut proposal = PROPOSALS.load(store, proposal_id)?;
+
+    let mut selection = match proposal.selection {
+        Some(selection) if selection.selected.is_none() => selection,
+        Some(_) => return Err(NeutronError::SelectionClosed{}),
+        None => return Err(NeutronError::InvalidSelection { reason: "proposal has no selection phase".to_string() }),
+    };
+
+    if !selection.ends.is_expired(&env.block) {
+        return Err(NeutronError::SelectionPending{});
+    }
+
+    let totals = get_proposal_funds(store, proposal_id, None)?;
+
+    let mut ranked : Vec<(Addr, Application, Decimal)> = vec![];
+
+    for item in APPLICATIONS.prefix(proposal_id).range(store, None, None, Order::Ascending).take(MAX_SELECTION_APPLICATIONS) {
+        let (application_sender, application) = item?;
+        if application.expired {
+            continue;
+        }
+        let mut weight = Decimal::zero();
+        for ((_, denom), funds) in get_application_locked_funds(store, proposal_id, &application_sender)? {
+            if let Some((_, funding)) = totals.iter().find(|(d, _)| *d == denom) {
+                weight += Decimal::from_ratio(funds.amount, funding.amount * Uint128::from(totals.len() as u128));
+            }
+        }
+        ranked.push((application_sender, application, weight));
+    }
+
+    // the sort is stable, ties are won by the earlier address
+    ranked.sort_by_key(|(_, _, weight)| std::cmp::Reverse(*weight));
+
+    let mut selected : Vec<Addr> = vec![];
+    let mut claimed : Vec<Coin> = vec![];
+    let mut claimed_all = false;
+
+    for (application_sender, application, weight) in ranked.iter() {
+        if selected.len() as u32 == selection.winners || claimed_all || weight.is_zero() {
+            break;
+        }
+
+        // an application without a budget claims all of the funding
+        if application.budget.is_empty() {
+            if selected.is_empty() {
+                claimed_all = true;
+                selected.push(application_sender.clone());
+            }
+            continue;
+        }
+
+        let fits = application.budget.iter().all(|b| {
+            let funded = totals.iter().find(|(d, _)| *d == b.denom).map(|(_, f)| f.amount).unwrap_or_default();
+            let used = claimed.iter().find(|c| c.denom == b.denom).map(|c| c.amount).unwrap_or_default();
+            used + b.amount <= funded
+        });
+
+        if fits {
+            for b in application.budget.iter() {
+                match claimed.iter_mut().find(|c| c.denom == b.denom) {
+                    Some(c) => c.amount += b.amount,
+                    None => claimed.push(b.clone()),
+                }
+            }
+            selected.push(application_sender.clone());
+        }
+    }
+
+    let mut released = 0;
+    for (application_sender, _, _) in ranked.iter().filter(|(a, _, _)| !selected.contains(a)) {
+        for ((funder, denom), funds) in get_application_locked_funds(store, proposal_id, application_sender)? {
+            CUSTODY_FUNDS.save(store, custody_key(&funder, proposal_id, &denom, funds.remote.as_deref()), &CustodyFunds {
+                locked: false,
+                application: None,
+                excess: Uint128::zero(),
+                ..funds.clone()
+            })?;
+            released += 1;
+        }
+    }
+
+    let event = crossfund_event("selection-finalized")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("selected", selected.iter().map(|a| a.as_str()).collect::<Vec<_>>().join(","))
+        .add_attribute("released", released.to_string());
+
+    selection.selected = Some(selected);
+    proposal.selection = Some(selection);
+    PROPOSALS.save(store, proposal_id, &proposal)?;
+
+    Ok(Response::default().add_event(event))
+}
+
+
+
+pub fn update_query_period(
+    store: &mut dyn Storage,
+    sender: Addr,
+    proposal_id: u64,
+    query_id: u64,
+    update_period: u64,
+) -> NeutronResponse {
+
+    let proposal = PROPOSALS.load(store, proposal_id)?;
+    let config = CONFIG.load(store)?;
+
+    if sender != config.admin && !proposal.is_creator(&sender) {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    if update_period == 0 {
+        return Err(NeutronError::Std(StdError::generic_err("Update period must be positive")));
+    }
+
+    let mut info = PROPOSAL_QUERIES.load(store, (proposal_id, query_id))?;
+    info.update_period = update_period;
+    PROPOSAL_QUERIES.save(store, (proposal_id, query_id), &info)?;
+
+    Ok(Response::new()
+        .add_message(NeutronMsg::update_interchain_query(query_id, None, Some(update_period), None)?)
+        .add_event(crossfund_event("query-period-updated")
+            .add_attribute("proposal_id", proposal_id.to_string())
+            .add_attribute("query_id", query_id.to_string())
+            .add_attribute("update_period", update_period.to_string()))
+    )
+}
+
+
+
+pub fn refund_quarantined(
+    mut deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> NeutronResponse {
+
+    let mut deposit = QUARANTINED_DEPOSITS.load(deps.storage, id)?;
+
+    if deposit.refund_pending {
+        return Err(NeutronError::RefundInProgress{});
+    }
+
+    if deposit.reason == QuarantineReason::UnknownSender {
+        return Err(NeutronError::Std(StdError::generic_err("Sender of the deposit can't receive a refund")));
+    }
+
+    if deposit.reason == QuarantineReason::UnknownAccount {
+        return Err(NeutronError::Std(StdError::generic_err("Deposit isn't held by an interchain account of the contract")));
+    }
+
+    // failed attempts stay in the outbox and are picked up by RetryPending
+    let outbox_id = next_operation_id(deps.storage)?;
+    let response = submit_operation(deps.branch(), &env, &info, outbox_id, PendingOperation {
+        port_id: deposit.port_id.clone(),
+        operation: IcaOperation::RefundQuarantined { id },
+        attempts: 0,
+        last_error: None,
+        in_flight: false,
+        retry_after: 0,
+    })?;
+
+    deposit.refund_pending = true;
+    QUARANTINED_DEPOSITS.save(deps.storage, id, &deposit)?;
+
+    Ok(response.add_event(crossfund_event("refund-requested")
+        .add_attribute("deposit_id", id.to_string())
+        .add_attribute("outbox_id", outbox_id.to_string())
+        .add_attribute("recipient", deposit.sender)
+        .add_attribute("denom", deposit.denom)
+        .add_attribute("amount", deposit.amount.to_string())
+    ))
+}
+
+
+
+pub fn retry_pending(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> NeutronResponse {
+
+    let pending = OUTBOX.load(deps.storage, id)?;
+
+    if pending.in_flight {
+        return Err(NeutronError::OperationInFlight{});
+    }
+
+    if env.block.height < pending.retry_after {
+        return Err(NeutronError::RetryNotDue { retry_after: pending.retry_after });
+    }
+
+    submit_operation(deps, &env, &info, id, pending)
+}
+
+
+
+/// Submits every failed operation that is due, the fees are paid from the reserve.
+/// Meant to be called by a cron schedule, operations that can't be submitted yet are skipped
+pub fn retry_due(
+    mut deps: DepsMut<NeutronQuery>,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> NeutronResponse {
+
+    nonpayable(&info).map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let limit = limit.unwrap_or(RETRY_BATCH_LIMIT).min(RETRY_BATCH_LIMIT) as usize;
+
+    let due : Vec<(u64, PendingOperation)> = OUTBOX
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter(|item| item.as_ref().map_or(true, |(_, op)| !op.in_flight && op.retry_after <= env.block.height))
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    let mut response = Response::new();
+
+    // one operation that can't be submitted, e.g. with a closed channel, doesn't block the others
+    for (id, pending) in due {
+        match submit_operation(deps.branch(), &env, &info, id, pending) {
+            Ok(res) => response = response.add_submessages(res.messages).add_events(res.events),
+            Err(e) => response = response.add_event(crossfund_event("operation-skipped")
+                .add_attribute("outbox_id", id.to_string())
+                .add_attribute("error", e.to_string())
+            ),
+        }
+    }
+
+    Ok(response)
+}
+
+
+
+pub fn schedule_retries(
+    store: &mut dyn Storage,
+    env: Env,
+    sender: Addr,
+    period: Option<u64>,
+) -> NeutronResponse {
+
+    if sender != CONFIG.load(store)?.admin {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    // adding and removing schedules is only allowed to the contracts whitelisted by the DAO
+    let msg = match period {
+        Some(period) => NeutronMsg::submit_add_schedule(
+            RETRY_SCHEDULE_NAME.to_string(),
+            period,
+            vec![MsgExecuteContract {
+                contract: env.contract.address.to_string(),
+                msg: serde_json_wasm::to_string(&ExecuteMsg::RetryDue { limit: None })
+                    .map_err(|e| StdError::generic_err(e.to_string()))?,
+            }],
+        ),
+        None => NeutronMsg::submit_remove_schedule(RETRY_SCHEDULE_NAME.to_string()),
+    };
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_event(crossfund_event("retries-scheduled")
+            .add_attribute("schedule", RETRY_SCHEDULE_NAME)
+            .add_attribute("period", period.map(|p| p.to_string()).unwrap_or_else(|| "none".to_string())))
+    )
+}
+
+
+
+/// Builds the interchain transaction of an outbox operation and sends it over the account's channel
+fn submit_operation(
+    deps: DepsMut<NeutronQuery>,
+    env: &Env,
+    info: &MessageInfo,
+    outbox_id: u64,
+    mut pending: PendingOperation,
+) -> NeutronResponse {
+
+    let ica = INTERCHAIN_ACCOUNTS.load(deps.storage, pending.port_id.clone())?;
+
+    if ica.status != IcaStatus::Open {
+        return Err(NeutronError::IcaNotOpen{});
+    }
+
+    let ica_address = ica.address.clone().ok_or_else(|| StdError::not_found("Interchain account address"))?;
+    let (message, msgs) = operation_msgs(deps.storage, &ica_address, &pending.operation)?;
+
+    let fee = min_ntrn_ibc_fee(query_min_ibc_fee(deps.as_ref())?.min_fee);
+    let (excess, fee_payment) = pay_ibc_fee(deps.storage, info, &fee)?;
+
+    let submit = NeutronMsg::submit_tx(
+        ica.connection_id,
+        ica_id_from_port(&pending.port_id),
+        msgs,
+        String::new(),
+        DEFAULT_TIMEOUT_SECONDS,
+        fee.clone(),
+    );
+
+    let reply_id = save_reply_payload(deps.storage, SudoPayload {
+        message,
+        port_id: pending.port_id.clone(),
+        operation: pending.operation.clone(),
+        fee,
+        outbox_id,
+        fee_payment,
+    })?;
+
+    pending.attempts += 1;
+    pending.in_flight = true;
+    pending.retry_after = env.block.height;
+    OUTBOX.save(deps.storage, outbox_id, &pending)?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(submit, reply_id))
+        .add_messages(excess)
+        .add_event(crossfund_event("operation-submitted")
+            .add_attribute("outbox_id", outbox_id.to_string())
+            .add_attribute("operation", pending.operation.name())
+            .add_attribute("port_id", pending.port_id)
+            .add_attribute("attempt", pending.attempts.to_string()))
+    )
+}
+
+
+
+fn operation_msgs(
+    store: &dyn Storage,
+    ica_address: &str,
+    operation: &IcaOperation,
+) -> Result<(String, Vec<ProtobufAny>), NeutronError> {
+
+    match operation {
+        IcaOperation::RefundQuarantined { id } => {
+            let deposit = QUARANTINED_DEPOSITS.load(store, *id)?;
+
+            let send = MsgSend {
+                from_address: deposit.recipient,
+                to_address: deposit.sender,
+                amount: vec![ProtoCoin {
+                    denom: deposit.denom,
+                    amount: deposit.amount.to_string(),
+                }],
+            };
+
+            let mut buf = Vec::with_capacity(send.encoded_len());
+            send.encode(&mut buf).map_err(|e| StdError::generic_err(e.to_string()))?;
+
+            Ok((operation.name().to_string(), vec![ProtobufAny {
+                type_url: COSMOS_SDK_TRANSFER_MSG_URL.to_string(),
+                value: Binary::from(buf),
+            }]))
+        }
+        IcaOperation::WithdrawCustody { recipient, denom, amount, .. } => {
+            let send = MsgSend {
+                from_address: ica_address.to_string(),
+                to_address: recipient.clone(),
+                amount: vec![ProtoCoin {
+                    denom: denom.clone(),
+                    amount: amount.to_string(),
+                }],
+            };
+
+            let mut buf = Vec::with_capacity(send.encoded_len());
+            send.encode(&mut buf).map_err(|e| StdError::generic_err(e.to_string()))?;
+
+            Ok((operation.name().to_string(), vec![ProtobufAny {
+                type_url: COSMOS_SDK_TRANSFER_MSG_URL.to_string(),
+                value: Binary::from(buf),
+            }]))
+        }
+    }
+}
+
+
+
+/// Removes the errors with ids up to and including `up_to`
+pub fn clear_errors(
+    store: &mut dyn Storage,
+    sender: Addr,
+    up_to: u32,
+) -> NeutronResponse {
+
+    if sender != CONFIG.load(store)?.admin {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    let ids = ERRORS_QUEUE
+        .keys(store, None, Some(Bound::inclusive(up_to)), Order::Ascending)
+        .collect::<StdResult<Vec<u32>>>()?;
+
+    for id in ids.iter() {
+        ERRORS_QUEUE.remove(store, *id)?;
+    }
+
+    Ok(Response::default().add_event(crossfund_event("errors-cleared")
+        .add_attribute("up_to", up_to.to_string())
+        .add_attribute("cleared", ids.len().to_string())
+    ))
+}
+
+
+
+pub fn fund_fee_reserve(
+    store: &mut dyn Storage,
+    info: MessageInfo,
+) -> NeutronResponse {
+
+    let paid = must_pay(&info, FEE_DENOM).map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let reserve = FEE_RESERVE.may_load(store)?.unwrap_or_default();
+    FEE_RESERVE.save(store, &(reserve + paid))?;
+
+    Ok(Response::default().add_event(crossfund_event("fee-reserve-funded")
+        .add_attribute("funder", info.sender.as_str())
+        .add_attribute("amount", coin(paid.u128(), FEE_DENOM).to_string())
+        .add_attribute("reserve", coin((reserve + paid).u128(), FEE_DENOM).to_string())
+    ))
+}
+
+
+
+/// Covers the fee of an interchain transaction with the funds attached by the caller 
+/// and takes whatever is missing from the fee reserve. The surplus is sent back to the caller,
+/// the part paid by the caller is returned with the payment so that refunds of the fee go back to them
+pub fn pay_ibc_fee(
+    store: &mut dyn Storage,
+    info: &MessageInfo,
+    fee: &IbcFee,
+) -> Result<(Vec<CosmosMsg<NeutronMsg>>, Option<FeePayment>), NeutronError> {
+
+    let required = total_ibc_fee(fee);
+    let paid = fee_denom_amount(&info.funds);
+
+    let payment = (!paid.is_zero()).then(|| FeePayment {
+        payer: info.sender.clone(),
+        amount: paid.min(required),
+    });
+
+    if paid >= required {
+        let surplus = paid - required;
+        if surplus.is_zero() {
+            return Ok((vec![], payment));
+        }
+        return Ok((vec![BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(surplus.u128(), FEE_DENOM),
+        }.into()], payment));
+    }
+
+    let missing = required - paid;
+    let reserve = FEE_RESERVE.may_load(store)?.unwrap_or_default();
+
+    if reserve < missing {
+        return Err(NeutronError::InsufficientFunds { required: coin(missing.u128(), FEE_DENOM).to_string() });
+    }
+
+    FEE_RESERVE.save(store, &(reserve - missing))?;
+
+    Ok((vec![], payment))
+}
+
+
+
+/// Sends back the unlocked funds of the sender and the excess of the locked ones.
+/// Funds held by an interchain account are queued in the outbox to be sent to the account
+/// of the sender on the remote chain, they are submitted with `RetryPending` or `RetryDue`
+pub fn withdraw_funds(
+    store: &mut dyn Storage,
+    env: Env,
+    info: MessageInfo,
+) -> NeutronResponse {
+
+    nonpayable(&info).map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let sender = info.sender;
+    let mut messages : Vec::<CosmosMsg<NeutronMsg>> = vec![];
+    let mut events = vec![];
+
+    // locked funds above the budget of the application are withdrawn without unlocking the rest
+    let funds = get_address_funds(store, &sender, false)?
+        .into_iter()
+        .filter(|(_, fund)| !fund.locked || !fund.excess.is_zero());
+
+    for (token, fund) in funds {
+        let key = custody_key(&sender, fund.proposal_id, &token, fund.remote.as_deref());
+        let amount = if fund.locked {
+            CUSTODY_FUNDS.save(store, key, &CustodyFunds {
+                amount: fund.amount - fund.excess,
+                excess: Uint128::zero(),
+                ..fund.clone()
+            })?;
+            fund.excess
+        } else {
+            CUSTODY_FUNDS.remove(store, key)?;
+            fund.amount
+        };
+
+        // the totals of the proposal only count the funds that are still there
+        if let Some(mut funding) = PROPOSAL_FUNDING.may_load(store, (fund.proposal_id, token.as_str()))? {
+            funding.amount = funding.amount.saturating_sub(amount);
+            PROPOSAL_FUNDING.save(store, (fund.proposal_id, token.as_str()), &funding)?;
+        }
+
+        match &fund.remote {
+            None => messages.push(BankMsg::Send {
+                to_address: sender.to_string(),
+                amount: coins(amount.u128(), &token),
+            }.into()),
+            Some(port_id) => {
+                let recipient = remote_address(store, port_id, &sender)?;
+                let outbox_id = next_operation_id(store)?;
+                OUTBOX.save(store, outbox_id, &PendingOperation {
+                    port_id: port_id.clone(),
+                    operation: IcaOperation::WithdrawCustody {
+                        funder: sender.clone(),
+                        proposal_id: fund.proposal_id,
+                        recipient,
+                        denom: token.clone(),
+                        amount,
+                    },
+                    attempts: 0,
+                    last_error: None,
+                    in_flight: false,
+                    retry_after: env.block.height,
+                })?;
+                events.push(crossfund_event("withdrawal-queued")
+                    .add_attribute("outbox_id", outbox_id.to_string())
+                    .add_attribute("proposal_id", fund.proposal_id.to_string())
+                    .add_attribute("denom", token.as_str())
+                    .add_attribute("amount", amount.to_string()));
+            }
+        }
+    }
+
+    if messages.is_empty() && events.is_empty() {
+        return Err(NeutronError::NoFunds{});
+    }
+
+    Ok(Response::default().add_messages(messages).add_events(events))
+}
+
+
+/// Address of the same account on the chain of the interchain account
+fn remote_address(
+    store: &dyn Storage,
+    port_id: &str,
+    address: &Addr,
+) -> StdResult<String> {
+    let ica_address = INTERCHAIN_ACCOUNTS
+        .load(store, port_id.to_string())?
+        .address
+        .ok_or_else(|| StdError::not_found("Interchain account address"))?;
+
+    let (prefix, _, _) = bech32::decode(&ica_address).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let (_, data, variant) = bech32::decode(address.as_str()).map_err(|e| StdError::generic_err(e.to_string()))?;
+    bech32::encode(&prefix, data, variant).map_err(|e| StdError::generic_err(e.to_string()))
+}
+
+
+fn reward_applicants(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+    application_sender: Addr,
+) -> StdResult<Vec<Event>> {
+
+    let application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
+    
+    let funds = get_application_locked_funds(store, proposal_id, &application_sender)?;
+
+    // the funds above the budget go back to the funders custody
+    for ((sender, token), fund) in &funds {
+        let contributed = fund.locked_amount();
+        if !contributed.is_zero() {
+            APPLICATION_CONTRIBUTIONS.update(store, (proposal_id, &application_sender, sender), |c| -> StdResult<_> {
+                let mut c = c.unwrap_or_default();
+                c.push(coin(contributed.u128(), token));
+                Ok(c)
+            })?;
+        }
+        let key = custody_key(sender, proposal_id, token, fund.remote.as_deref());
+        if fund.excess.is_zero() {
+            CUSTODY_FUNDS.remove(store, key)?;
+        } else {
+            CUSTODY_FUNDS.save(store, key, &CustodyFunds {
+                amount: fund.excess,
+                locked: false,
+                application: None,
+                excess: Uint128::zero(),
+                ..fund.clone()
+            })?;
+        }
+    }
+
+    let (legs, _) = payout_legs(&application, &funds);
+    let mut events = Vec::with_capacity(legs.len());
+
+    for leg in legs.into_iter().filter(|leg| !leg.amount.is_zero()) {
+        record_reputation(store, std::slice::from_ref(&leg.recipient), |stats| {
+            match stats.earned.iter_mut().find(|c| c.denom == leg.denom) {
+                Some(earned) => earned.amount += leg.amount,
+                None => stats.earned.push(coin(leg.amount.u128(), &leg.denom)),
+            }
+        })?;
+
+        let mut event = crossfund_event("payout")
+            .add_attribute("proposal_id", proposal_id.to_string())
+            .add_attribute("application", application_sender.as_str())
+            .add_attribute("recipient", leg.recipient.as_str())
+            .add_attribute("denom", leg.denom.as_str())
+            .add_attribute("amount", leg.amount.to_string())
+            .add_attribute("kind", if leg.remote.is_some() { "remote" } else { "native" });
+
+        // custody is withdrawn by the sender, recipients of other chains hold it under their Neutron address
+        let holder = if is_remote_recipient(&leg.recipient) {
+            local_address(leg.recipient.as_str())?
+        } else {
+            leg.recipient.clone()
+        };
+
+        match &leg.remote {
+            // funds of an interchain account are sent to remote recipients right away,
+            // a transfer that keeps failing goes back to custody
+            Some(port_id) if holder != leg.recipient => {
+                let outbox_id = next_operation_id(store)?;
+                OUTBOX.save(store, outbox_id, &PendingOperation {
+                    port_id: port_id.clone(),
+                    operation: IcaOperation::WithdrawCustody {
+                        funder: holder,
+                        proposal_id,
+                        recipient: leg.recipient.to_string(),
+                        denom: leg.denom.clone(),
+                        amount: leg.amount,
+                    },
+                    attempts: 0,
+                    last_error: None,
+                    in_flight: false,
+                    retry_after: 0,
+                })?;
+                event = event.add_attribute("outbox_id", outbox_id.to_string());
+            },
+            // a recipient can already hold funds of the proposal, e.g. as one of its funders
+            _ => {
+                CUSTODY_FUNDS.update(store, custody_key(&holder, proposal_id, &leg.denom, leg.remote.as_deref()), |f| -> StdResult<_> {
+                    Ok(add_custody(f, leg.amount, proposal_id, leg.remote.clone()))
+                })?;
+            },
+        }
+
+        events.push(event);
+    }
+
+    Ok(events)
+}
+
+
+fn add_query_credits(
+    store: &mut dyn Storage,
+    port_id: &str,
+    payer: &Addr,
+    deposit: Uint128,
+    count: usize,
+) -> StdResult<()> {
+    let mut credits = QUERY_CREDITS.may_load(store, port_id)?.unwrap_or_default();
+    credits.extend((0..count).map(|_| QueryDeposit {
+        payer: payer.clone(),
+        amount: coin(deposit.u128(), FEE_DENOM),
+    }));
+    QUERY_CREDITS.save(store, port_id, &credits)
+}
+
+
+fn refund_deposit_msg(deposit: QueryDeposit) -> CosmosMsg<NeutronMsg> {
+    BankMsg::Send {
+        to_address: deposit.payer.to_string(),
+        amount: vec![deposit.amount],
+    }.into()
+}
+
+
+/// Moves the proposal to the terminal state and removes its interchain queries.
+/// Deposits returned by Neutron and unused prepaid deposits go back to their payers
+fn complete_proposal(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+) -> StdResult<Vec<CosmosMsg<NeutronMsg>>> {
+
+    PROPOSALS.update(store, proposal_id, |p| match p {
+        Some(mut p) => {
+            p.status = ProposalStatus::Completed;
+            Ok(p)
+        },
+        None => Err(StdError::not_found("Proposal"))
+    })?;
+
+    let queries = PROPOSAL_QUERIES
+        .prefix(proposal_id)
+        .range(store, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut messages: Vec<CosmosMsg<NeutronMsg>> = Vec::with_capacity(queries.len());
+    let mut refunds: Vec<CosmosMsg<NeutronMsg>> = vec![];
+
+    for (query_id, info) in queries {
+        PROPOSAL_QUERIES.remove(store, (proposal_id, query_id));
+        QUERY_TO_PROPOSAL.remove(store, query_id);
+        if let QueryKind::Balance { denom } = &info.kind {
+            BALANCE_QUERY_IDS.remove(store, (info.port_id.as_str(), denom.as_str()));
+        }
+        messages.push(NeutronMsg::remove_interchain_query(query_id).into());
+        refunds.extend(info.deposit.map(refund_deposit_msg));
+    }
+
+    let ports = PROPOSAL_ICAS
+        .prefix(proposal_id)
+        .range(store, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, port_id)| port_id))
+        .collect::<StdResult<Vec<String>>>()?;
+
+    for port_id in ports {
+        let credits = QUERY_CREDITS.may_load(store, port_id.as_str())?.unwrap_or_default();
+        QUERY_CREDITS.remove(store, port_id.as_str());
+        refunds.extend(credits.into_iter().map(refund_deposit_msg));
+    }
+
+    // removals come first so that the deposits are back on the contract balance
+    messages.extend(refunds);
+    Ok(messages)
+}
+
+
+/// Auditors can't profit from the application they verify. Depending on the rules
+/// they can't be its sender or applicants and can't be among the major funders of the proposal
+fn check_auditor_conflicts(
+    store: &dyn Storage,
+    rules: &ConflictRules,
+    proposal_id: u64,
+    application_sender: &Addr,
+    applicants: &[GoodFee],
+    auditor: &Addr,
+) -> Result<(), NeutronError> {
+
+    if rules.separate_auditors {
+        if auditor == application_sender {
+            return Err(NeutronError::AuditorIsSender { auditor: auditor.to_string() });
+        }
+        if applicants.iter().any(|gf| gf.recipient == *auditor) {
+            return Err(NeutronError::AuditorIsApplicant { auditor: auditor.to_string() });
+        }
+    }
+
+    if let Some(max_bps) = rules.max_auditor_funding_bps {
+        // deposits of a denom on different accounts count together
+        let mut funded : Vec<(String, Uint128)> = vec![];
+        for item in CUSTODY_FUNDS.prefix((auditor, proposal_id)).range(store, None, None, Order::Ascending) {
+            let ((denom, _), funds) = item?;
+            match funded.iter_mut().find(|(d, _)| *d == denom) {
+                Some((_, amount)) => *amount += funds.amount,
+                None => funded.push((denom, funds.amount)),
+            }
+        }
+
+        for (denom, amount) in funded {
+            let total = get_proposal_funds_token(store, proposal_id, &denom)?;
+            if !total.is_zero() && amount.multiply_ratio(TOTAL_SHARE_BPS, total) > Uint128::from(max_bps) {
+                return Err(NeutronError::AuditorIsMajorFunder { auditor: auditor.to_string(), denom });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Accepted and not expired application of the given sender or the one the sender is an applicant of
+fn applicant_application(
+    store: &dyn Storage,
+    sender: &Addr,
+    proposal_id: u64,
+    application_sender: Option<Addr>,
+) -> Result<(Addr, Application), NeutronError> {
+
+    let application_sender = match application_sender {
+        Some(application_sender) => application_sender,
+        None => {
+            let applications = PARTICIPANT_APPLICATIONS
+                .prefix((sender, proposal_id))
+                .range(store, None, None, Order::Ascending)
+                .filter(|item| item.as_ref().map_or(true, |(_, roles)| roles.contains(&ParticipantRole::Applicant)))
+                .take(2)
+                .collect::<StdResult<Vec<_>>>()?;
+
+            match &applications[..] {
+                [(application_sender, _)] => application_sender.clone(),
+                [] => return Err(NeutronError::NonAuthorized{}),
+                _ => return Err(NeutronError::Std(StdError::generic_err("Sender applied more than once, specify the application"))),
+            }
+        }
+    };
+
+    let application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
+
+    if application.applicants.iter().all(|a| a.recipient != sender) {
+        return Err(NeutronError::NonAuthorized{});
+    }
+
+    if !application.accepted {
+        return Err(NeutronError::Std(StdError::generic_err("Application is not accepted")));
+    }
+
+    if application.expired {
+        return Err(NeutronError::ApplicationExpired{});
+    }
+
+    Ok((application_sender, application))
+}
+
+
+fn check_update_text(text: &str) -> Result<(), NeutronError> {
+    if text.trim().is_empty() {
+        return Err(NeutronError::InvalidUpdate { reason: "empty text".to_string() });
+    }
+    if text.len() > MAX_UPDATE_TEXT_LENGTH {
+        return Err(NeutronError::InvalidUpdate { reason: format!("text longer than {} bytes", MAX_UPDATE_TEXT_LENGTH) });
+    }
+    Ok(())
+}
+
+
+/// Locks only the budget of an application, split between the funders pro-rata to the approved amounts.
+/// The rest of the approved funds stays withdrawable. Returns the locked amount per budgeted denom
+fn lock_budget(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+    application_sender: &Addr,
+    budget: &[Coin],
+) -> StdResult<Vec<Coin>> {
+
+    let funds = get_application_locked_funds(store, proposal_id, application_sender)?;
+    let mut locked = Vec::with_capacity(budget.len());
+
+    // denoms approved before the budget was set aren't claimed by the application
+    if !budget.is_empty() {
+        for ((funder, denom), f) in funds.iter().filter(|((_, denom), _)| budget.iter().all(|b| b.denom != *denom)) {
+            CUSTODY_FUNDS.save(store, custody_key(funder, proposal_id, denom, f.remote.as_deref()), &CustodyFunds {
+                locked: false,
+                application: None,
+                excess: Uint128::zero(),
+                ..f.clone()
+            })?;
+            APPLICATION_FUNDING.remove(store, (proposal_id, application_sender.clone(), denom.as_str()));
+        }
+    }
+
+    for budget in budget {
+        let denom_funds : Vec<_> = funds.iter().filter(|((_, denom), _)| *denom == budget.denom).collect();
+        let total : Uint128 = denom_funds.iter().map(|(_, f)| f.amount).sum();
+        let target = budget.amount.min(total);
+
+        let mut shares : Vec<Uint128> = denom_funds
+            .iter()
+            .map(|(_, f)| target.multiply_ratio(f.amount, total))
+            .collect();
+
+        // rounding down leaves a few units of the budget, they go to the funders with room left
+        let mut rest = target - shares.iter().copied().sum::<Uint128>();
+        for (share, (_, f)) in shares.iter_mut().zip(&denom_funds) {
+            let extra = rest.min(f.amount - *share);
+            *share += extra;
+            rest -= extra;
+        }
+
+        for (share, ((funder, denom), f)) in shares.into_iter().zip(denom_funds) {
+            CUSTODY_FUNDS.save(store, custody_key(funder, proposal_id, denom, f.remote.as_deref()), &CustodyFunds {
+                excess: f.amount - share,
+                ..f.clone()
+            })?;
+        }
+
+        locked.push(coin(target.u128(), &budget.denom));
+    }
+
+    Ok(locked)
+}
+
+
+/// Accepted application of a bounty hunter with the auditors of the bounty
+fn open_bounty_submission(
+    store: &mut dyn Storage,
+    hunter: &Addr,
+    proposal_id: u64,
+    proposal: &Proposal,
+    bounty: &Bounty,
+) -> Result<(), NeutronError> {
+
+    let auditors_share : u32 = bounty.auditors.iter().map(|gf| u32::from(gf.share_bps)).sum();
+    let applicants = vec![GoodFee {
+        recipient: hunter.clone(),
+        share_bps: (TOTAL_SHARE_BPS - auditors_share) as u16,
+    }];
+
+    let config = CONFIG.load(store)?;
+    for auditor in bounty.auditors.iter() {
+        check_auditor_conflicts(store, &config.conflict_rules, proposal_id, hunter, &applicants, &auditor.recipient)?;
+    }
+
+    if let Some(requirement) = &proposal.min_reputation {
+        if !requirement.is_met(&REPUTATION.may_load(store, hunter)?.unwrap_or_default()) {
+            return Err(NeutronError::ReputationTooLow { applicant: hunter.to_string() });
+        }
+    }
+
+    let application = Application {
+        applicants,
+        auditors: bounty.auditors.clone(),
+        deliver_by: Expiration::Never {},
+        accepted: true,
+        verifications: vec![],
+        expired: false,
+        deliverables: vec![],
+        budget: vec![],
+    };
+
+    record_reputation(store, std::slice::from_ref(hunter), |stats| {
+        stats.submitted += 1;
+        stats.accepted += 1;
+    })?;
+    save_participant_roles(store, proposal_id, hunter, &application)?;
+    APPLICATIONS.save(store, (proposal_id, hunter.clone()), &application)?;
+    Ok(())
+}
+
+
+/// Locks all funds of the bounty for the winning submission and closes the other submissions
+fn close_bounty(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+    winner: &Addr,
+) -> StdResult<Event> {
+
+    let funds = CUSTODY_FUNDS
+        .idx
+        .proposal
+        .prefix(proposal_id)
+        .range(store, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for ((funder, _, (denom, account)), funds) in funds {
+        // the funders of the pool back the winner and can dispute the verifications
+        APPLICATION_BACKERS.save(store, (proposal_id, winner, &funder), &funds.remote.is_some())?;
+        CUSTODY_FUNDS.save(store, (&funder, proposal_id, (denom.as_str(), account.as_str())), &CustodyFunds {
+            locked: true,
+            application: Some(winner.clone()),
+            excess: Uint128::zero(),
+            ..funds
+        })?;
+    }
+
+    // the other submissions are left as they are, the completed bounty rejects their deliveries and verifications
+    Ok(crossfund_event("bounty-closed")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("winner", winner.as_str()))
+}
+
+
+fn check_selected(
+    selection: &Selection,
+    application_sender: &Addr,
+) -> Result<(), NeutronError> {
+    match &selection.selected {
+        None => Err(NeutronError::SelectionPending{}),
+        Some(selected) if !selected.contains(application_sender) => Err(NeutronError::NotSelected{}),
+        Some(_) => Ok(()),
+    }
+}
+
+
+/// Whether every selected application was either fully verified or expired
+fn selection_settled(
+    store: &dyn Storage,
+    proposal_id: u64,
+    selected: &[Addr],
+) -> StdResult<bool> {
+    for application_sender in selected {
+        let application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
+        if !application.expired && application.verifications.len() < application.auditors.len() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+
+fn clear_extension(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+    application_sender: &Addr,
+) -> StdResult<()> {
+    EXTENSION_REQUESTS.remove(store, (proposal_id, application_sender));
+
+    let voters = EXTENSION_VOTES
+        .prefix((proposal_id, application_sender))
+        .keys(store, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    for voter in voters.iter() {
+        EXTENSION_VOTES.remove(store, (proposal_id, application_sender, voter));
+    }
+    Ok(())
+}
+
+
+/// Distinct addresses taking part in an application
+fn participants(application: &Application) -> Vec<Addr> {
+    let mut addresses : Vec<Addr> = vec![];
+    for gf in shareholders(application) {
+        if !addresses.contains(&gf.recipient) {
+            addresses.push(gf.recipient);
+        }
+    }
+    addresses
+}
+
+
+fn record_reputation(
+    store: &mut dyn Storage,
+    addresses: &[Addr],
+    update: impl Fn(&mut ReputationStats),
+) -> StdResult<()> {
+    for address in addresses {
+        let mut stats = REPUTATION.may_load(store, address)?.unwrap_or_default();
+        update(&mut stats);
+        REPUTATION.save(store, address, &stats)?;
+    }
+    Ok(())
+}
+
+
+fn check_registered_auditor(
+    store: &dyn Storage,
+    rules: &BondRules,
+    auditor: &Addr,
+) -> Result<(), NeutronError> {
+    match AUDITORS.may_load(store, auditor)? {
+        Some(profile) if profile.is_registered(rules) => Ok(()),
+        _ => Err(NeutronError::AuditorNotRegistered { auditor: auditor.to_string() }),
+    }
+}
+
+
+fn check_for_auto_agree(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+    application_sender: Addr,
+) -> StdResult<bool> {
+    let mut agreed = false;
+    for (token, amount) in  get_application_funds(store, proposal_id, application_sender.clone())? {
+        let total = get_proposal_funds_token(store, proposal_id, token.as_str())?;
+        if total.is_zero() {
+            continue;
+        }
+        let ratio = Decimal::from_ratio(amount, total);
+        if ratio > Decimal::percent(50) {
+            auto_agree(store, proposal_id, &application_sender)?;
+            agreed = true;
+        }
+    }
+    Ok(agreed)
+}
+
+
+fn auto_agree(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+    application_sender: &Addr,
+) -> StdResult<()> {
+
+    let application = APPLICATIONS.load(store, (proposal_id, application_sender.clone()))?;
+    let funds = get_proposal_funds(store, proposal_id, Some(true))?;
+
+    for (token, funding) in funds.iter().filter(|(token, _)| application.claims(token)) {
+
+        // the funder may have withdrawn the funds or locked them for another application
+        let custody = CUSTODY_FUNDS
+            .prefix((&funding.sender, proposal_id))
+            .range(store, None, None, Order::Ascending)
+            .filter(|item| item.as_ref().map_or(true, |((denom, _), f)| denom == token && !f.locked))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut agreed = Uint128::zero();
+        for ((denom, account), mut f) in custody {
+            f.locked = true;
+            f.application = Some(application_sender.clone());
+            agreed += f.amount;
+            CUSTODY_FUNDS.save(store, (&funding.sender, proposal_id, (denom.as_str(), account.as_str())), &f)?;
+            APPLICATION_BACKERS.save(store, (proposal_id, application_sender, &funding.sender), &f.remote.is_some())?;
+        }
+
+        if !agreed.is_zero() {
+            APPLICATION_FUNDING.update(store, (proposal_id, application_sender.clone(), token.as_str()), |f| -> StdResult<Uint128> {
+                Ok(f.unwrap_or_default() + agreed)
+            })?;
+        }
+    }
+
+    Ok(())
 }
\ No newline at end of file
@@ -8,7 +8,6 @@ pub mod msg;
 pub mod query;
 pub mod storage;
 pub mod sudo;
-pub mod temp;
 pub mod utils;
 
 #[allow(clippy::unwrap_used)]
@@ -1,9 +1,13 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Response};
+use cosmwasm_std::{Addr, Coin, Response, Uint128};
 use cw_utils::Expiration;
 use neutron_sdk::{bindings::msg::NeutronMsg, NeutronResult};
 
-use crate::storage::{AcknowledgementResult, Application, CustodyFunds, GoodFee, ProjectFunding};
+use crate::storage::{
+    AcknowledgementResult, Application, AuditorProfile, BondRules, Config, ConflictRules, Dispute, ExtensionRequest, ProgressUpdate, ReputationRequirement, ReputationStats, CustodyFunds, GoodFee, IcaStatus, InterchainAccount,
+    InterchainQueryInfo,
+    ParticipantRole, ProjectFunding, ProposalStatus, Selection, Bounty, QuarantinedDeposit, QueryDeposit, IbcFeeRecord, PendingOperation, ErrorKind, ErrorRecord,
+};
 
 pub type NeutronResponse = NeutronResult<Response<NeutronMsg>>;
 
@@ -11,11 +15,53 @@ pub type NeutronResponse = NeutronResult<Response<NeutronMsg>>;
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     #[returns(AllProposalResponse)]
-    AllProposals {},
+    AllProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order: Option<ListOrder>,
+        filter: Option<ProposalFilter>,
+    },
+
+    /// Same as `AllProposals` but without the applications of every proposal
+    #[returns(ProposalSummariesResponse)]
+    ProposalSummaries {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order: Option<ListOrder>,
+        filter: Option<ProposalFilter>,
+    },
 
     #[returns(FullProposalInfo)]
     Proposal { proposal_id: u64 },
 
+    #[returns(Application)]
+    Application { proposal_id: u64, sender: String },
+
+    #[returns(Vec<(Addr, Application)>)]
+    Applications {
+        proposal_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(Vec<(String, Uint128)>)]
+    ApplicationFunding { proposal_id: u64, sender: String },
+
+    /// Who would get what if the application were verified now
+    #[returns(PayoutSimulation)]
+    SimulatePayout {
+        proposal_id: u64,
+        application_sender: String,
+    },
+
+    /// Applications where the address is an applicant or an auditor
+    #[returns(Vec<ParticipantApplication>)]
+    ApplicationsByParticipant {
+        address: String,
+        start_after: Option<(u64, String)>,
+        limit: Option<u32>,
+    },
+
     #[returns(Vec<(String, CustodyFunds)>)]
     AddressFunds {
         address: Addr,
@@ -28,27 +74,117 @@ pub enum QueryMsg {
         proposal_id: u64,
     },
 
-    #[returns((String, String))]
-    InterchainAccountAddressFromContract { proposal_id: u64 },
+    #[returns(InterchainAccount)]
+    InterchainAccountAddressFromContract {
+        proposal_id: u64,
+        connection_id: String,
+    },
+
+    #[returns(Vec<DepositAddress>)]
+    DepositAddresses { proposal_id: u64 },
 
     #[returns(Option<AcknowledgementResult>)]
-    AcknowledgementResult { sequence_id: u64, proposal_id: u64 },
+    AcknowledgementResult {
+        sequence_id: u64,
+        proposal_id: u64,
+        connection_id: String,
+    },
+
+    #[returns(Vec<(u32, ErrorRecord)>)]
+    ErrorsQueue {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+        kind: Option<ErrorKind>,
+    },
+
+    #[returns(Vec<(u64, InterchainQueryInfo)>)]
+    ProposalQueries { proposal_id: u64 },
+
+    #[returns(Config)]
+    Config {},
+
+    #[returns(ReconciliationResponse)]
+    Reconciliation { proposal_id: u64 },
+
+    #[returns(Vec<(u64, QuarantinedDeposit)>)]
+    QuarantinedDeposits {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    #[returns(Coin)]
+    FeeReserve {},
 
-    #[returns(Vec<(Vec<u8>, String)>)]
-    ErrorsQueue {},
+    /// Registration fees collected and not withdrawn yet
+    #[returns(Coin)]
+    RegistrationFees {},
+
+    #[returns(Vec<(u64, PendingOperation)>)]
+    PendingOperations {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        stuck_only: Option<bool>,
+    },
+
+    #[returns(Option<IbcFeeRecord>)]
+    IbcFee { channel_id: String, sequence: u64 },
+
+    #[returns(Option<AuditorProfile>)]
+    Auditor { address: String },
+
+    #[returns(Vec<(Addr, AuditorProfile)>)]
+    Auditors {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(Option<ExtensionRequest>)]
+    ExtensionRequest { proposal_id: u64, application_sender: String },
+
+    #[returns(Vec<(u64, ProgressUpdate)>)]
+    Updates {
+        proposal_id: u64,
+        application_sender: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    #[returns(ReputationStats)]
+    Reputation { address: String },
+
+    #[returns(Dispute)]
+    Dispute { id: u64 },
+
+    #[returns(Vec<(u64, Dispute)>)]
+    Disputes {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
 pub struct MigrateMsg {}
 
 #[cw_serde]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    /// Defaults to the sender of the instantiate message
+    pub admin: Option<String>,
+    /// Defaults to zero
+    pub registration_fee: Option<Uint128>,
+    /// Defaults to the deposit required by the interchain queries module of Neutron
+    pub query_deposit: Option<Uint128>,
+    /// Defaults to auditors being separate from the applicants without funding limits
+    pub conflict_rules: Option<ConflictRules>,
+    /// Defaults to a bond of 1 NTRN that can be fully slashed
+    pub bond_rules: Option<BondRules>,
+}
 
 #[cw_serde]
 pub enum ExecuteMsg {
     SubmitProposal {
         title: String,
         description: String,
+        tags: Option<Vec<String>>,
     },
     SubmitApplication {
         proposal_id: u64,
@@ -57,6 +193,8 @@ pub enum ExecuteMsg {
     FundProposal {
         proposal_id: u64,
         auto_agree: Option<bool>,
+        /// Only registered auditors can be part of the applications once set
+        require_registered_auditors: Option<bool>,
     },
     RegisterICA {
         proposal_id: u64,
@@ -78,11 +216,145 @@ pub enum ExecuteMsg {
         stop_at: Option<u64>,
     },
 
-    TempRegister {
+    RefundQuarantined {
+        id: u64,
+    },
+
+    /// Sends back the unlocked funds of the sender and the locked funds above the budget of an application
+    WithdrawFunds {},
+
+    FundFeeReserve {},
+
+    ClearErrors {
+        up_to: u32,
+    },
+
+    RetryPending {
+        id: u64,
+    },
+
+    RetryDue {
+        limit: Option<u32>,
+    },
+
+    ScheduleRetries {
+        period: Option<u64>,
+    },
+
+    SponsorQueries {
+        proposal_id: u64,
+        connection_id: String,
+    },
+
+    UpdateFees {
+        registration_fee: Uint128,
+        query_deposit: Uint128,
+    },
+
+    WithdrawRegistrationFees {
+        /// Defaults to the admin
+        recipient: Option<String>,
+    },
+
+    UpdateConflictRules {
+        rules: ConflictRules,
+    },
+
+    RegisterAuditor {
+        profile_uri: String,
+    },
+
+    UnbondAuditor {},
+
+    ClaimAuditorBond {},
+
+    UpdateBondRules {
+        rules: BondRules,
+    },
+
+    SubmitDeliverable {
+        proposal_id: u64,
+        /// Defaults to the application the sender is an applicant of
+        application_sender: Option<Addr>,
+        uri: String,
+        /// Hex encoded SHA-256 of the delivered content
+        content_hash: String,
+    },
+
+    RequestExtension {
+        proposal_id: u64,
+        /// Defaults to the application the sender is an applicant of
+        application_sender: Option<Addr>,
+        deliver_by: Expiration,
+        reason: String,
+    },
+
+    VoteExtension {
+        proposal_id: u64,
+        application_sender: Addr,
+        approve: bool,
+    },
+
+    ExpireApplication {
+        proposal_id: u64,
+        application_sender: Addr,
+    },
+
+    PostUpdate {
+        proposal_id: u64,
+        /// Defaults to the application the sender is an applicant of
+        application_sender: Option<Addr>,
+        uri: String,
+        summary: String,
+    },
+
+    CommentUpdate {
+        proposal_id: u64,
+        application_sender: Addr,
+        update_id: u64,
+        comment: String,
+    },
+
+    SetMinReputation {
+        proposal_id: u64,
+        requirement: Option<ReputationRequirement>,
+    },
+
+    OpenSelection {
+        proposal_id: u64,
+        ends: Expiration,
+        winners: u32,
+    },
+
+    SelectApplications {
+        proposal_id: u64,
+    },
+
+    OpenBounty {
+        proposal_id: u64,
+        auditors: Vec<GoodFee>,
+    },
+
+    RaiseDispute {
+        proposal_id: u64,
+        application_sender: Addr,
+        reason: String,
+    },
+
+    ResolveDispute {
+        id: u64,
+        upheld: bool,
+    },
+
+    SetConnectionChain {
         connection_id: String,
-        recipient: String,
+        chain_id: String,
+    },
+
+    UpdateQueryPeriod {
+        proposal_id: u64,
+        query_id: u64,
         update_period: u64,
-        min_height: Option<u64>,
     },
 }
 
@@ -91,6 +363,9 @@ pub struct ApplicationSubmission {
     pub applicants: Vec<GoodFee>,
     pub auditors: Vec<GoodFee>,
     pub deliver_by: Expiration,
+    /// Requested amount per denom, all approved funds are claimed when empty
+    #[serde(default)]
+    pub budget: Vec<Coin>,
 }
 
 #[cw_serde]
@@ -98,11 +373,118 @@ pub struct FullProposalInfo {
     pub id: u64,
     pub title: String,
     pub description: String,
+    pub creator: Option<Addr>,
+    pub status: ProposalStatus,
+    pub tags: Vec<String>,
+    pub selection: Option<Selection>,
+    pub bounty: Option<Bounty>,
     pub funding: Vec<(String, ProjectFunding)>,
     pub applications: Vec<(Addr, Application)>,
 }
 
+#[cw_serde]
+pub struct ParticipantApplication {
+    pub proposal_id: u64,
+    pub sender: Addr,
+    pub roles: Vec<ParticipantRole>,
+    pub application: Application,
+}
+
+#[cw_serde]
+pub struct PayoutLeg {
+    pub recipient: Addr,
+    pub denom: String,
+    pub amount: Uint128,
+    /// Port of the interchain account holding the funds, `None` for a native bank send
+    pub remote: Option<String>,
+}
+
+#[cw_serde]
+pub struct PayoutSimulation {
+    pub proposal_id: u64,
+    pub application_sender: Addr,
+    pub legs: Vec<PayoutLeg>,
+    /// Left over after rounding the shares down
+    pub dust: Vec<Coin>,
+}
+
 #[cw_serde]
 pub struct AllProposalResponse {
     pub proposals: Vec<FullProposalInfo>,
+    /// Pass as `start_after` to continue the listing, `None` once every proposal was looked at
+    pub next_start_after: Option<u64>,
+}
+
+#[cw_serde]
+pub struct ProposalSummary {
+    pub id: u64,
+    pub title: String,
+    pub creator: Option<Addr>,
+    pub status: ProposalStatus,
+    pub tags: Vec<String>,
+    pub funding: Vec<(String, ProjectFunding)>,
+}
+
+#[cw_serde]
+pub struct ProposalSummariesResponse {
+    pub proposals: Vec<ProposalSummary>,
+    /// Pass as `start_after` to continue the listing, `None` once every proposal was looked at
+    pub next_start_after: Option<u64>,
+}
+
+#[cw_serde]
+pub enum ListOrder {
+    Ascending,
+    Descending,
+}
+
+/// Every set field has to match for a proposal to be listed
+#[cw_serde]
+#[derive(Default)]
+pub struct ProposalFilter {
+    pub status: Option<ProposalStatus>,
+    pub creator: Option<String>,
+    /// Proposals funded with the denom
+    pub denom: Option<String>,
+    pub tag: Option<String>,
+    /// Proposals the address has put funds into
+    pub funded_by: Option<String>,
+}
+
+#[cw_serde]
+pub struct DenomReconciliation {
+    pub denom: String,
+    /// Sum of remote custody funds attributed to the interchain account
+    pub custody: Uint128,
+    /// Last balance reported by the KV query, `None` until the first result arrives
+    pub on_chain: Option<Uint128>,
+    pub last_submitted_local_height: Option<u64>,
+    pub surplus: Uint128,
+    pub deficit: Uint128,
+}
+
+#[cw_serde]
+pub struct AccountReconciliation {
+    pub connection_id: String,
+    pub port_id: String,
+    pub denoms: Vec<DenomReconciliation>,
+}
+
+#[cw_serde]
+pub struct ReconciliationResponse {
+    pub proposal_id: u64,
+    pub accounts: Vec<AccountReconciliation>,
+}
+
+#[cw_serde]
+pub struct DepositAddress {
+    pub connection_id: String,
+    /// Chain id registered by the admin for the connection
+    pub chain_id: Option<String>,
+    pub port_id: String,
+    /// Remote address of the interchain account, `None` until the channel is open
+    pub address: Option<String>,
+    pub status: IcaStatus,
+    /// Deposits paid in advance for the interchain queries of the account
+    pub prepaid_query_deposits: Vec<QueryDeposit>,
 }
@@ -1,23 +1,130 @@
-use cosmwasm_std::{Storage, Order, StdResult, Uint128, Addr, Deps, Env, Binary, to_binary, CustomQuery, StdError};
+use cosmwasm_std::{Storage, Order, StdResult, Uint128, Addr, Deps, Env, Binary, to_binary, CustomQuery, StdError, coin};
+use cw_storage_plus::Bound;
 use neutron_sdk::{bindings::query::{NeutronQuery, QueryInterchainAccountAddressResponse}, NeutronResult, interchain_txs::helpers::get_port_id};
 
-use crate::{storage::{PROPOSALS, PROPOSAL_FUNDING, APPLICATIONS, Proposal, Application, APPLICATION_FUNDING, ProjectFunding, CustodyFunds, CUSTODY_FUNDS, INTERCHAIN_ACCOUNTS, ACKNOWLEDGEMENT_RESULTS, read_errors_from_queue}, msg::{AllProposalResponse, FullProposalInfo}};
+use crate::{storage::{PROPOSALS, PROPOSAL_FUNDING, APPLICATIONS, Proposal, Application, APPLICATION_FUNDING, ProjectFunding, CustodyFunds, CUSTODY_FUNDS, INTERCHAIN_ACCOUNTS, ACKNOWLEDGEMENT_RESULTS, read_errors_from_queue, QUARANTINED_DEPOSITS, QuarantinedDeposit, REMOTE_BALANCES, PROPOSAL_QUERIES, InterchainQueryInfo, CONFIG, PROPOSAL_ICAS, CONNECTION_CHAINS, InterchainAccount, QUERY_CREDITS, FEE_RESERVE, REGISTRATION_FEES, IBC_FEES, OUTBOX, PendingOperation, ErrorKind, PARTICIPANT_APPLICATIONS, AUDITORS, AuditorProfile, REPUTATION, EXTENSION_REQUESTS, UPDATES, ProgressUpdate, DISPUTES, Dispute}, msg::{AllProposalResponse, ParticipantApplication, PayoutSimulation, FullProposalInfo, ListOrder, ProposalFilter, ProposalSummary, ProposalSummariesResponse, DenomReconciliation, ReconciliationResponse, AccountReconciliation, DepositAddress}, utils::{ica_id, payout_legs, validate_recipient, FEE_DENOM}};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+// proposals looked at by one listing query, filters matching few proposals need several pages
+const MAX_PROPOSALS_SCANNED: usize = 100;
 
 
 
 pub fn query_all_proposals(
-    store: &dyn Storage,
+    deps: Deps<NeutronQuery>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order: Option<ListOrder>,
+    filter: Option<ProposalFilter>,
 ) -> NeutronResult<Binary> {
 
-    let full_proposal_infos : Vec<FullProposalInfo> = PROPOSALS
-            .range(store, None, None, Order::Ascending)
-            .map(|f| {
-                let (id, proposal) = f.unwrap();
-                get_proposal_full_info(store, id, &proposal).unwrap()
-            })
-            .collect();
+    let (proposals, next_start_after) = list_proposals(deps, start_after, limit, order, filter)?;
+
+    let full_proposal_infos : Vec<FullProposalInfo> = proposals
+            .iter()
+            .map(|(id, proposal)| get_proposal_full_info(deps.storage, *id, proposal))
+            .collect::<StdResult<_>>()?;
+
+    Ok(to_binary(&AllProposalResponse { proposals: full_proposal_infos, next_start_after })?)
+}
+
+
+pub fn query_proposal_summaries(
+    deps: Deps<NeutronQuery>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order: Option<ListOrder>,
+    filter: Option<ProposalFilter>,
+) -> NeutronResult<Binary> {
+
+    let (proposals, next_start_after) = list_proposals(deps, start_after, limit, order, filter)?;
+
+    let summaries : Vec<ProposalSummary> = proposals
+            .into_iter()
+            .map(|(id, proposal)| Ok(ProposalSummary {
+                id,
+                title: proposal.title,
+                creator: proposal.creator,
+                status: proposal.status,
+                tags: proposal.tags,
+                funding: get_proposal_funds(deps.storage, id, None)?,
+            }))
+            .collect::<StdResult<_>>()?;
+
+    Ok(to_binary(&ProposalSummariesResponse { proposals: summaries, next_start_after })?)
+}
+
+
+type ProposalPage = (Vec<(u64, Proposal)>, Option<u64>);
+
+/// Returns the matching proposals and the id to continue from when the scan stopped before the end
+fn list_proposals(
+    deps: Deps<NeutronQuery>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order: Option<ListOrder>,
+    filter: Option<ProposalFilter>,
+) -> StdResult<ProposalPage> {
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let filter = filter.unwrap_or_default();
+
+    let (min, max, order) = match order.unwrap_or(ListOrder::Ascending) {
+        ListOrder::Ascending => (start_after.map(Bound::exclusive), None, Order::Ascending),
+        ListOrder::Descending => (None, start_after.map(Bound::exclusive), Order::Descending),
+    };
+
+    let creator = filter.creator
+        .map(|creator| deps.api.addr_validate(&creator))
+        .transpose()?;
+
+    // proposals the address has funds in custody for
+    let funded : Option<Vec<u64>> = filter.funded_by
+        .map(|funder| -> StdResult<Vec<u64>> {
+            let funder = deps.api.addr_validate(&funder)?;
+            let mut ids = CUSTODY_FUNDS
+                .sub_prefix(&funder)
+                .keys(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(proposal_id, _)| proposal_id))
+                .collect::<StdResult<Vec<u64>>>()?;
+            ids.dedup();
+            Ok(ids)
+        })
+        .transpose()?;
+
+    let tag = filter.tag.map(|tag| tag.trim().to_lowercase());
+
+    let mut proposals = vec![];
+    let mut scanned = 0;
+    let mut last_scanned = None;
+
+    for item in PROPOSALS.range(deps.storage, min, max, order).take(MAX_PROPOSALS_SCANNED) {
+        let (id, proposal) = item?;
+        scanned += 1;
+        last_scanned = Some(id);
+
+        let matches = filter.status.as_ref().is_none_or(|status| proposal.status == *status)
+            && creator.as_ref().is_none_or(|creator| proposal.is_creator(creator))
+            && tag.as_ref().is_none_or(|tag| proposal.tags.contains(tag))
+            && funded.as_ref().is_none_or(|funded| funded.contains(&id))
+            && filter.denom.as_ref().is_none_or(|denom| PROPOSAL_FUNDING.has(deps.storage, (id, denom.as_str())));
+
+        if matches {
+            proposals.push((id, proposal));
+            if proposals.len() == limit {
+                break;
+            }
+        }
+    }
+
+    let next_start_after = if proposals.len() == limit || scanned == MAX_PROPOSALS_SCANNED {
+        last_scanned
+    } else {
+        None
+    };
 
-    Ok(to_binary(&AllProposalResponse { proposals: full_proposal_infos })?)
+    Ok((proposals, next_start_after))
 }
 
 
@@ -37,7 +144,7 @@ fn get_proposal_full_info(
     proposal: &Proposal
 ) -> StdResult<FullProposalInfo> {
 
-    if PROPOSALS.has(store, id) == false {
+    if !PROPOSALS.has(store, id) {
         return Err(StdError::generic_err("Proposal does not exist"));
     }
 
@@ -48,6 +155,11 @@ fn get_proposal_full_info(
         id,
         title: proposal.title.clone(),
         description: proposal.description.clone(),
+        creator: proposal.creator.clone(),
+        status: proposal.status.clone(),
+        tags: proposal.tags.clone(),
+        selection: proposal.selection.clone(),
+        bounty: proposal.bounty.clone(),
         funding,
         applications
     })
@@ -60,20 +172,14 @@ pub fn get_proposal_funds(
     proposal_id: u64,
     auto_agree_only: Option<bool>
 ) -> StdResult<Vec<(String, ProjectFunding)>> {
-    Ok(PROPOSAL_FUNDING
+    PROPOSAL_FUNDING
         .prefix(proposal_id)
         .range(store, None, None, Order::Ascending)
-        .filter(|f| {
-            let (_, funding) = f.as_ref().unwrap();
-            if let Some(auto_agree_only) = auto_agree_only {
-                funding.auto_agree == auto_agree_only
-            } else {
-                true
-            }
+        .filter(|f| match (f, auto_agree_only) {
+            (Ok((_, funding)), Some(auto_agree_only)) => funding.auto_agree == auto_agree_only,
+            _ => true
         })
-        .map(|f| f.unwrap())
-        .collect::<Vec<_>>()
-    )
+        .collect::<StdResult<Vec<_>>>()
 }
 
 pub fn get_proposal_funds_token(
@@ -89,12 +195,104 @@ fn get_proposal_applications(
     store: &dyn Storage,
     proposal_id: u64
 ) -> StdResult<Vec<(Addr, Application)>> {
-    Ok(APPLICATIONS
+    APPLICATIONS
         .prefix(proposal_id)
         .range(store, None, None, Order::Ascending)
-        .map(|f| f.unwrap())
-        .collect::<Vec<_>>()
-    )
+        .collect::<StdResult<Vec<_>>>()
+}
+
+
+pub fn query_application(
+    deps: Deps<NeutronQuery>,
+    proposal_id: u64,
+    sender: String,
+) -> NeutronResult<Binary> {
+    let sender = deps.api.addr_validate(&sender)?;
+    Ok(to_binary(&APPLICATIONS.load(deps.storage, (proposal_id, sender))?)?)
+}
+
+
+pub fn query_applications(
+    deps: Deps<NeutronQuery>,
+    proposal_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|sender| deps.api.addr_validate(&sender))
+        .transpose()?;
+
+    let applications = APPLICATIONS
+        .prefix(proposal_id)
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(Addr, Application)>>>()?;
+
+    Ok(to_binary(&applications)?)
+}
+
+
+pub fn query_application_funding(
+    deps: Deps<NeutronQuery>,
+    proposal_id: u64,
+    sender: String,
+) -> NeutronResult<Binary> {
+    let sender = deps.api.addr_validate(&sender)?;
+    Ok(to_binary(&get_application_funds(deps.storage, proposal_id, sender)?)?)
+}
+
+
+pub fn query_applications_by_participant(
+    deps: Deps<NeutronQuery>,
+    address: String,
+    start_after: Option<(u64, String)>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // shares can go to addresses of other chains
+    let address = validate_recipient(deps.api, &Addr::unchecked(address))?;
+    let start_after = start_after
+        .map(|(proposal_id, sender)| deps.api.addr_validate(&sender).map(|sender| (proposal_id, sender)))
+        .transpose()?;
+
+    let applications = PARTICIPANT_APPLICATIONS
+        .sub_prefix(&address)
+        .range(
+            deps.storage,
+            start_after.as_ref().map(|(proposal_id, sender)| Bound::exclusive((*proposal_id, sender))),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let ((proposal_id, sender), roles) = item?;
+            let application = APPLICATIONS.load(deps.storage, (proposal_id, sender.clone()))?;
+            Ok(ParticipantApplication { proposal_id, sender, roles, application })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(to_binary(&applications)?)
+}
+
+
+pub fn query_simulate_payout(
+    deps: Deps<NeutronQuery>,
+    proposal_id: u64,
+    application_sender: String,
+) -> NeutronResult<Binary> {
+    let application_sender = deps.api.addr_validate(&application_sender)?;
+    let application = APPLICATIONS.load(deps.storage, (proposal_id, application_sender.clone()))?;
+    let funds = get_application_locked_funds(deps.storage, proposal_id, &application_sender)?;
+
+    let (legs, dust) = payout_legs(&application, &funds);
+
+    Ok(to_binary(&PayoutSimulation {
+        proposal_id,
+        application_sender,
+        legs,
+        dust,
+    })?)
 }
 
 
@@ -104,11 +302,10 @@ pub fn get_application_funds(
     application_sender: Addr,
 ) -> StdResult<Vec<(String, Uint128)>> {
 
-    Ok(APPLICATION_FUNDING
+    APPLICATION_FUNDING
         .prefix((proposal_id, application_sender))
         .range(store, None, None, Order::Ascending)
-        .map(|f| f.unwrap())
-        .collect::<Vec<_>>())
+        .collect::<StdResult<Vec<_>>>()
 }
 
 
@@ -119,11 +316,28 @@ pub fn get_application_funds_token(
     token: String
 ) -> StdResult<Uint128> {
 
-    Ok(APPLICATION_FUNDING.load(store, (proposal_id, application_sender, token.as_str()))?)
+    APPLICATION_FUNDING.load(store, (proposal_id, application_sender, token.as_str()))
 }
 
 
 
+/// Funds locked for the application, found through the custody index instead of scanning all custody funds
+pub fn get_application_locked_funds(
+    store: &dyn Storage,
+    proposal_id: u64,
+    application_sender: &Addr,
+) -> StdResult<Vec<((Addr, String), CustodyFunds)>> {
+    CUSTODY_FUNDS
+        .idx
+        .application
+        .prefix((proposal_id, application_sender.clone()))
+        .range(store, None, None, Order::Ascending)
+        .filter(|f| f.as_ref().map_or(true, |(_, funds)| funds.locked))
+        .map(|f| f.map(|((funder, _, (denom, _)), funds)| ((funder, denom), funds)))
+        .collect()
+}
+
+
 pub fn query_address_funds(
     store: &dyn Storage,
     address: &Addr,
@@ -143,13 +357,12 @@ pub fn get_address_funds(
     address: &Addr,
     skip_locked: bool
 ) -> StdResult<Vec<(String, CustodyFunds)>> {
-    Ok(CUSTODY_FUNDS
-        .prefix(address)
+    CUSTODY_FUNDS
+        .sub_prefix(address)
         .range(store, None, None, Order::Ascending)
-        .map(|f| f.unwrap())
-        .filter(|(_, custody_funds)| !(skip_locked && custody_funds.locked))
-        .collect::<Vec<(String, CustodyFunds)>>()
-    )
+        .filter(|f| f.as_ref().map_or(true, |(_, custody_funds)| !(skip_locked && custody_funds.locked)))
+        .map(|f| f.map(|((_, (denom, _)), custody_funds)| (denom, custody_funds)))
+        .collect::<StdResult<Vec<(String, CustodyFunds)>>>()
 }
 
 
@@ -161,7 +374,7 @@ pub fn query_interchain_address(
 ) -> NeutronResult<Binary> {
     let query = NeutronQuery::InterchainAccountAddress {
         owner_address: env.contract.address.to_string(),
-        interchain_account_id: proposal_id.to_string(),
+        interchain_account_id: ica_id(proposal_id, &connection_id),
         connection_id,
     };
     let res: QueryInterchainAccountAddressResponse = deps.querier.query(&query.into())?;
@@ -172,9 +385,43 @@ pub fn query_interchain_address(
 pub fn query_interchain_address_contract(
     deps: Deps<NeutronQuery>,
     env: Env,
-    proposal_id: u64
+    proposal_id: u64,
+    connection_id: String,
+) -> NeutronResult<Binary> {
+    Ok(to_binary(&get_ica(deps, &env, proposal_id, &connection_id)?)?)
+}
+
+
+pub fn query_deposit_addresses(
+    store: &dyn Storage,
+    proposal_id: u64,
 ) -> NeutronResult<Binary> {
-    Ok(to_binary(&get_ica(deps, &env, &proposal_id)?)?)
+    Ok(to_binary(&get_deposit_addresses(store, proposal_id)?)?)
+}
+
+
+pub fn get_deposit_addresses(
+    store: &dyn Storage,
+    proposal_id: u64,
+) -> StdResult<Vec<DepositAddress>> {
+    PROPOSAL_ICAS
+        .prefix(proposal_id)
+        .range(store, None, None, Order::Ascending)
+        .map(|item| {
+            let (connection_id, port_id) = item?;
+            let ica = INTERCHAIN_ACCOUNTS.load(store, port_id.clone())?;
+            Ok(DepositAddress {
+                chain_id: CONNECTION_CHAINS.may_load(store, connection_id.as_str())?,
+                connection_id,
+                address: ica.address,
+                status: ica.status,
+                prepaid_query_deposits: QUERY_CREDITS
+                    .may_load(store, port_id.as_str())?
+                    .unwrap_or_default(),
+                port_id,
+            })
+        })
+        .collect()
 }
 
 
@@ -183,29 +430,297 @@ pub fn query_acknowledgement_result(
     env: Env,
     sequence_id: u64,
     proposal_id: u64,
+    connection_id: String,
 ) -> NeutronResult<Binary> {
-    let port_id: String = get_port_id(env.contract.address.as_str(), &proposal_id.to_string());
+    let port_id: String = get_port_id(env.contract.address.as_str(), &ica_id(proposal_id, &connection_id));
     let res = ACKNOWLEDGEMENT_RESULTS.may_load(deps.storage, (port_id, sequence_id))?;
     Ok(to_binary(&res)?)
 }
 
 
-pub fn query_errors_queue(deps: Deps<NeutronQuery>) -> NeutronResult<Binary> {
-    let res = read_errors_from_queue(deps.storage)?;
+pub fn query_errors_queue(
+    store: &dyn Storage,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+    kind: Option<ErrorKind>,
+) -> NeutronResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let res = read_errors_from_queue(store, start_after, limit, kind)?;
     Ok(to_binary(&res)?)
 }
 
+pub fn query_fee_reserve(
+    store: &dyn Storage,
+) -> NeutronResult<Binary> {
+    let reserve = FEE_RESERVE.may_load(store)?.unwrap_or_default();
+    Ok(to_binary(&coin(reserve.u128(), FEE_DENOM))?)
+}
+
+
+pub fn query_registration_fees(
+    store: &dyn Storage,
+) -> NeutronResult<Binary> {
+    let collected = REGISTRATION_FEES.may_load(store)?.unwrap_or_default();
+    Ok(to_binary(&coin(collected.u128(), FEE_DENOM))?)
+}
+
+
+pub fn query_ibc_fee(
+    store: &dyn Storage,
+    channel_id: String,
+    sequence: u64,
+) -> NeutronResult<Binary> {
+    Ok(to_binary(&IBC_FEES.may_load(store, (channel_id, sequence))?)?)
+}
+
+
+pub fn query_auditor(
+    deps: Deps<NeutronQuery>,
+    address: String,
+) -> NeutronResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(to_binary(&AUDITORS.may_load(deps.storage, &address)?)?)
+}
+
+
+pub fn query_auditors(
+    deps: Deps<NeutronQuery>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+
+    let auditors = AUDITORS
+        .range(deps.storage, start_after.as_ref().map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(Addr, AuditorProfile)>>>()?;
+
+    Ok(to_binary(&auditors)?)
+}
+
+
+/// Open extension request of the application, lapsed requests are left out
+pub fn query_extension_request(
+    deps: Deps<NeutronQuery>,
+    env: Env,
+    proposal_id: u64,
+    application_sender: String,
+) -> NeutronResult<Binary> {
+    let application_sender = deps.api.addr_validate(&application_sender)?;
+    let request = EXTENSION_REQUESTS
+        .may_load(deps.storage, (proposal_id, &application_sender))?
+        .filter(|r| r.voting_ends >= env.block.height);
+    Ok(to_binary(&request)?)
+}
+
+
+/// Progress updates of an application starting from the oldest one
+pub fn query_updates(
+    deps: Deps<NeutronQuery>,
+    proposal_id: u64,
+    application_sender: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let application_sender = deps.api.addr_validate(&application_sender)?;
+
+    let updates = UPDATES
+        .prefix((proposal_id, &application_sender))
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(u64, ProgressUpdate)>>>()?;
+
+    Ok(to_binary(&updates)?)
+}
+
+
+pub fn query_reputation(
+    deps: Deps<NeutronQuery>,
+    address: String,
+) -> NeutronResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(to_binary(&REPUTATION.may_load(deps.storage, &address)?.unwrap_or_default())?)
+}
+
+
+pub fn query_dispute(
+    store: &dyn Storage,
+    id: u64,
+) -> NeutronResult<Binary> {
+    Ok(to_binary(&DISPUTES.load(store, id)?)?)
+}
+
+
+pub fn query_disputes(
+    store: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let disputes = DISPUTES
+        .range(store, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(u64, Dispute)>>>()?;
+
+    Ok(to_binary(&disputes)?)
+}
+
+
+pub fn query_config(
+    store: &dyn Storage,
+) -> NeutronResult<Binary> {
+    Ok(to_binary(&CONFIG.load(store)?)?)
+}
+
+
+pub fn query_proposal_queries(
+    store: &dyn Storage,
+    proposal_id: u64,
+) -> NeutronResult<Binary> {
+    let queries = PROPOSAL_QUERIES
+        .prefix(proposal_id)
+        .range(store, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(u64, InterchainQueryInfo)>>>()?;
+    Ok(to_binary(&queries)?)
+}
+
+
+pub fn query_reconciliation(
+    deps: Deps<NeutronQuery>,
+    proposal_id: u64,
+) -> NeutronResult<Binary> {
+    let accounts = PROPOSAL_ICAS
+        .prefix(proposal_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (connection_id, port_id) = item?;
+            Ok(AccountReconciliation {
+                denoms: reconcile_account(deps.storage, &port_id)?,
+                connection_id,
+                port_id,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(to_binary(&ReconciliationResponse {
+        proposal_id,
+        accounts,
+    })?)
+}
+
+
+fn reconcile_account(
+    store: &dyn Storage,
+    port_id: &str,
+) -> StdResult<Vec<DenomReconciliation>> {
+
+    let mut custody: Vec<(String, Uint128)> = vec![];
+
+    let remote = CUSTODY_FUNDS
+        .idx
+        .remote
+        .prefix(port_id.to_string())
+        .range(store, None, None, Order::Ascending);
+
+    for item in remote {
+        let ((_, _, (denom, _)), funds) = item?;
+        match custody.iter_mut().find(|(d, _)| d == &denom) {
+            Some((_, sum)) => *sum += funds.amount,
+            None => custody.push((denom, funds.amount)),
+        }
+    }
+
+    let mut denoms: Vec<DenomReconciliation> = custody
+        .into_iter()
+        .map(|(denom, custody)| DenomReconciliation {
+            denom,
+            custody,
+            on_chain: None,
+            last_submitted_local_height: None,
+            surplus: Uint128::zero(),
+            deficit: Uint128::zero(),
+        })
+        .collect();
+
+    for item in REMOTE_BALANCES.prefix(port_id).range(store, None, None, Order::Ascending) {
+        let (denom, balance) = item?;
+        let position = match denoms.iter().position(|d| d.denom == denom) {
+            Some(position) => position,
+            None => {
+                denoms.push(DenomReconciliation {
+                    denom,
+                    custody: Uint128::zero(),
+                    on_chain: None,
+                    last_submitted_local_height: None,
+                    surplus: Uint128::zero(),
+                    deficit: Uint128::zero(),
+                });
+                denoms.len() - 1
+            }
+        };
+        let entry = &mut denoms[position];
+        entry.on_chain = Some(balance.amount);
+        entry.last_submitted_local_height = Some(balance.last_submitted_local_height);
+        entry.surplus = balance.amount.saturating_sub(entry.custody);
+        entry.deficit = entry.custody.saturating_sub(balance.amount);
+    }
+
+    Ok(denoms)
+}
+
+
+pub fn query_quarantined_deposits(
+    store: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> NeutronResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let deposits = QUARANTINED_DEPOSITS
+        .range(store, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(u64, QuarantinedDeposit)>>>()?;
+
+    Ok(to_binary(&deposits)?)
+}
+
+
+/// Operations of the outbox, `stuck_only` leaves out the ones waiting for an acknowledgement
+pub fn query_pending_operations(
+    store: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    stuck_only: bool,
+) -> NeutronResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let operations = OUTBOX
+        .range(store, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .filter(|item| !stuck_only || item.as_ref().map_or(true, |(_, op)| !op.in_flight))
+        .take(limit)
+        .collect::<StdResult<Vec<(u64, PendingOperation)>>>()?;
+
+    Ok(to_binary(&operations)?)
+}
+
+
 fn get_ica(
     deps: Deps<impl CustomQuery>,
     env: &Env,
-    proposal_id: &u64
-) -> Result<(String, String), StdError> {
+    proposal_id: u64,
+    connection_id: &str,
+) -> Result<InterchainAccount, StdError> {
     let key = get_port_id(
         env.contract.address.as_str(), 
-        proposal_id.to_string().as_str()
+        ica_id(proposal_id, connection_id).as_str()
     );
 
     INTERCHAIN_ACCOUNTS
-        .load(deps.storage, key)?
+        .may_load(deps.storage, key)?
         .ok_or_else(|| StdError::generic_err("Interchain account is not created yet"))
 }
\ No newline at end of file
@@ -1,18 +1,84 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{from_binary, to_vec, Addr, Binary, Order, StdResult, Storage, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{from_binary, from_slice, to_vec, Addr, Binary, Coin, Decimal, Order, StdError, StdResult, Storage, Timestamp, Uint128};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, KeyDeserialize, Map, MultiIndex};
 use cw_utils::Expiration;
+use neutron_sdk::bindings::msg::IbcFee;
+
+use crate::utils::{is_remote_recipient, local_address};
 
 #[cw_serde]
 pub struct SudoPayload {
     pub message: String,
     pub port_id: String,
+    pub operation: IcaOperation,
+    pub fee: IbcFee,
+    pub outbox_id: u64,
+    #[serde(default)]
+    pub fee_payment: Option<FeePayment>,
+}
+
+/// Part of an IBC fee paid with the funds attached by a caller, the rest comes from the fee reserve
+#[cw_serde]
+pub struct FeePayment {
+    pub payer: Addr,
+    pub amount: Uint128,
+}
+
+/// Fee escrowed for an interchain transaction and the part of it given back by the fee module
+#[cw_serde]
+pub struct IbcFeeRecord {
+    pub fee: IbcFee,
+    /// `None` while the packet is in flight
+    pub refunded: Option<Vec<Coin>>,
+    /// `None` when the fee reserve paid all of the fee
+    #[serde(default)]
+    pub payment: Option<FeePayment>,
 }
 
+/// Interchain operation kept in the outbox until it's acknowledged
+#[cw_serde]
+pub struct PendingOperation {
+    pub port_id: String,
+    pub operation: IcaOperation,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub in_flight: bool,
+    /// Block height from which a failed operation can be submitted again
+    pub retry_after: u64,
+}
+
+/// Business operation behind an interchain transaction, resolved once the ack / error / timeout arrives
+#[cw_serde]
+pub enum IcaOperation {
+    /// Sending a quarantined deposit back to its original sender
+    RefundQuarantined { id: u64 },
+    /// Sending funds withdrawn from custody to the account of the funder on the remote chain
+    WithdrawCustody {
+        funder: Addr,
+        proposal_id: u64,
+        recipient: String,
+        denom: String,
+        amount: Uint128,
+    },
+}
+
+impl IcaOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IcaOperation::RefundQuarantined { .. } => "refund_quarantined",
+            IcaOperation::WithdrawCustody { .. } => "withdraw_custody",
+        }
+    }
+}
+
+/// Shares are expressed in basis points, the shares of an application add up to `TOTAL_SHARE_BPS`
+pub const TOTAL_SHARE_BPS: u32 = 10_000;
+
 #[cw_serde]
 pub struct GoodFee {
+    /// Local address or a bech32 address on a remote chain
     pub recipient: Addr,
-    pub percent_share: u8,
+    pub share_bps: u16,
 }
 
 #[cw_serde]
@@ -40,6 +106,160 @@ pub struct CustodyFunds {
     pub proposal_id: u64,
     pub locked: bool,
     pub remote: Option<String>,
+    /// Application the funds have been locked for
+    #[serde(default)]
+    pub application: Option<Addr>,
+    /// Part of locked funds above the budget of the application, it stays withdrawable
+    #[serde(default)]
+    pub excess: Uint128,
+}
+
+impl CustodyFunds {
+    pub fn locked_amount(&self) -> Uint128 {
+        if self.locked {
+            self.amount - self.excess
+        } else {
+            Uint128::zero()
+        }
+    }
+}
+
+/// (funder, proposal id, (denom, port id of the interchain account or empty for native funds))
+pub type CustodyKey<'a> = (&'a Addr, u64, (&'a str, &'a str));
+/// Deserialized `CustodyKey`
+pub type CustodyPk = (Addr, u64, (String, String));
+
+pub fn custody_key<'a>(funder: &'a Addr, proposal_id: u64, denom: &'a str, remote: Option<&'a str>) -> CustodyKey<'a> {
+    (funder, proposal_id, (denom, remote.unwrap_or_default()))
+}
+
+pub struct CustodyIndexes<'a> {
+    pub proposal: MultiIndex<'a, u64, CustodyFunds, CustodyPk>,
+    pub application: MultiIndex<'a, (u64, Addr), CustodyFunds, CustodyPk>,
+    pub remote: MultiIndex<'a, String, CustodyFunds, CustodyPk>,
+}
+
+impl<'a> IndexList<CustodyFunds> for CustodyIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<CustodyFunds>> + '_> {
+        let v: Vec<&dyn Index<CustodyFunds>> =
+            vec![&self.proposal, &self.application, &self.remote];
+        Box::new(v.into_iter())
+    }
+}
+
+fn custody_proposal_idx(_pk: &[u8], funds: &CustodyFunds) -> u64 {
+    funds.proposal_id
+}
+
+fn custody_application_idx(_pk: &[u8], funds: &CustodyFunds) -> (u64, Addr) {
+    // funds that aren't locked for an application are indexed under an empty address
+    (
+        funds.proposal_id,
+        funds.application.clone().unwrap_or_else(|| Addr::unchecked("")),
+    )
+}
+
+fn custody_remote_idx(_pk: &[u8], funds: &CustodyFunds) -> String {
+    funds.remote.clone().unwrap_or_default()
+}
+
+#[cw_serde]
+pub enum ParticipantRole {
+    Applicant,
+    Auditor,
+}
+
+#[cw_serde]
+pub struct Config {
+    pub admin: Addr,
+    /// Fee in untrn kept by the contract for every interchain account registration
+    pub registration_fee: Uint128,
+    /// Deposit in untrn for every interchain query, returned to the payer when the query is removed
+    pub query_deposit: Uint128,
+    #[serde(default)]
+    pub conflict_rules: ConflictRules,
+    #[serde(default)]
+    pub bond_rules: BondRules,
+}
+
+/// Separation between the auditors of an application and the parties that profit from it
+#[cw_serde]
+pub struct ConflictRules {
+    /// Auditors can't be applicants or the sender of the application
+    pub separate_auditors: bool,
+    /// Auditors can't have funded more than this share of the proposal in any denom, in basis points
+    pub max_auditor_funding_bps: Option<u16>,
+}
+
+impl Default for ConflictRules {
+    fn default() -> Self {
+        ConflictRules {
+            separate_auditors: true,
+            max_auditor_funding_bps: None,
+        }
+    }
+}
+
+/// Requirements for the bonds of registered auditors, bonds are in untrn
+#[cw_serde]
+pub struct BondRules {
+    pub min_bond: Uint128,
+    /// Blocks an auditor waits after unbonding before the bond can be claimed
+    pub unbonding_period: u64,
+    /// Part of the bond taken by an upheld dispute, in basis points
+    pub slash_bps: u16,
+}
+
+impl Default for BondRules {
+    fn default() -> Self {
+        BondRules {
+            min_bond: Uint128::new(1_000_000),
+            unbonding_period: 100_000,
+            slash_bps: 10_000,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct AuditorProfile {
+    pub profile_uri: String,
+    pub bond: Uint128,
+    /// Block height from which the bond of an unbonding auditor can be claimed
+    pub unbonding_until: Option<u64>,
+}
+
+impl AuditorProfile {
+    pub fn is_registered(&self, rules: &BondRules) -> bool {
+        self.unbonding_until.is_none() && !self.bond.is_zero() && self.bond >= rules.min_bond
+    }
+}
+
+#[cw_serde]
+pub enum DisputeStatus {
+    Open,
+    Upheld,
+    Rejected,
+}
+
+/// Complaint of a funder about a verified application, resolved by the admin
+#[cw_serde]
+pub struct Dispute {
+    pub proposal_id: u64,
+    pub application_sender: Addr,
+    pub raised_by: Addr,
+    pub reason: String,
+    pub status: DisputeStatus,
+    /// Sum of the bonds taken from the auditors when the dispute is upheld
+    pub slashed: Uint128,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum ProposalStatus {
+    #[default]
+    Open,
+    /// Funds of a verified application have been distributed
+    Completed,
 }
 
 #[cw_serde]
@@ -47,6 +267,51 @@ pub struct Proposal {
     pub title: String,
     pub description: String,
     pub funding: Vec<ProjectFunding>,
+    /// `None` for proposals submitted before their creators were recorded
+    #[serde(default)]
+    pub creator: Option<Addr>,
+    #[serde(default)]
+    pub status: ProposalStatus,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Set by funders to only accept auditors from the registry
+    #[serde(default)]
+    pub require_registered_auditors: bool,
+    #[serde(default)]
+    pub min_reputation: Option<ReputationRequirement>,
+    #[serde(default)]
+    pub selection: Option<Selection>,
+    #[serde(default)]
+    pub bounty: Option<Bounty>,
+}
+
+impl Proposal {
+    pub fn is_creator(&self, address: &Addr) -> bool {
+        self.creator.as_ref() == Some(address)
+    }
+
+    /// Whether the funding is the pool of a bounty that nobody won yet
+    pub fn bounty_open(&self) -> bool {
+        self.bounty.is_some() && self.status != ProposalStatus::Completed
+    }
+}
+
+/// Bounty proposals pay all of their funding to the first verified submission.
+/// The auditors get their shares and the rest goes to the submitter
+#[cw_serde]
+pub struct Bounty {
+    pub auditors: Vec<GoodFee>,
+}
+
+/// Competitive bidding, applications are collected until the end of the selection
+/// and the ones with the most approvals are picked
+#[cw_serde]
+pub struct Selection {
+    pub ends: Expiration,
+    /// Maximum number of applications that can be selected
+    pub winners: u32,
+    /// Set once the selection is finalized
+    pub selected: Option<Vec<Addr>>,
 }
 
 #[cw_serde]
@@ -57,6 +322,180 @@ pub struct Application {
 
     pub accepted: bool,
     pub verifications: Vec<Addr>,
+    /// Set once the deadline passed without the work being verified
+    #[serde(default)]
+    pub expired: bool,
+    /// Every version of the work submitted by the applicants, the last one is the current
+    #[serde(default)]
+    pub deliverables: Vec<Deliverable>,
+    /// Amounts requested per denom, without a budget the application claims all approved funds
+    #[serde(default)]
+    pub budget: Vec<Coin>,
+}
+
+impl Application {
+    /// Whether funds of the denom can be approved for the application
+    pub fn claims(&self, denom: &str) -> bool {
+        self.budget.is_empty() || self.budget.iter().any(|c| c.denom == denom)
+    }
+}
+
+/// Share in whole percents, stored before shares were kept in basis points
+#[cw_serde]
+struct LegacyGoodFee {
+    recipient: Addr,
+    percent_share: u8,
+}
+
+impl From<LegacyGoodFee> for GoodFee {
+    fn from(fee: LegacyGoodFee) -> Self {
+        GoodFee {
+            recipient: fee.recipient,
+            share_bps: u16::from(fee.percent_share) * 100,
+        }
+    }
+}
+
+/// Application with shares in whole percents, converted by the migration
+#[cw_serde]
+struct LegacyApplication {
+    applicants: Vec<LegacyGoodFee>,
+    auditors: Vec<LegacyGoodFee>,
+    deliver_by: Expiration,
+    accepted: bool,
+    verifications: Vec<Addr>,
+}
+
+impl From<LegacyApplication> for Application {
+    fn from(application: LegacyApplication) -> Self {
+        Application {
+            applicants: application.applicants.into_iter().map(GoodFee::from).collect(),
+            auditors: application.auditors.into_iter().map(GoodFee::from).collect(),
+            deliver_by: application.deliver_by,
+            accepted: application.accepted,
+            verifications: application.verifications,
+            expired: false,
+            deliverables: vec![],
+            budget: vec![],
+        }
+    }
+}
+
+/// Request of the applicants to move the deadline, decided by the funders of the application
+#[cw_serde]
+pub struct ExtensionRequest {
+    pub deliver_by: Expiration,
+    pub reason: String,
+    pub requested_by: Addr,
+    /// Block height after which the request lapses and the deadline stays the same
+    pub voting_ends: u64,
+    /// Weights of the votes at the last tally, every denom counts equally
+    pub votes_for: Decimal,
+    pub votes_against: Decimal,
+}
+
+#[cw_serde]
+pub struct Deliverable {
+    pub uri: String,
+    /// Hex encoded SHA-256 of the delivered content
+    pub content_hash: String,
+    pub submitted_by: Addr,
+    pub submitted_at: Timestamp,
+}
+
+/// Progress report posted by an applicant while working on an accepted application
+#[cw_serde]
+pub struct ProgressUpdate {
+    pub uri: String,
+    pub summary: String,
+    pub posted_by: Addr,
+    pub posted_at: Timestamp,
+    /// Review comments of the auditors, in the order they were made
+    pub comments: Vec<UpdateComment>,
+}
+
+#[cw_serde]
+pub struct UpdateComment {
+    pub auditor: Addr,
+    pub comment: String,
+    pub posted_at: Timestamp,
+}
+
+/// History of an address across the applications it took part in
+#[cw_serde]
+#[derive(Default)]
+pub struct ReputationStats {
+    pub submitted: u32,
+    pub accepted: u32,
+    pub delivered: u32,
+    pub expired: u32,
+    /// Applications with an upheld dispute against them
+    pub disputed: u32,
+    pub earned: Vec<Coin>,
+}
+
+/// Requirements for the reputation of the applicants of a proposal
+#[cw_serde]
+pub struct ReputationRequirement {
+    pub min_delivered: u32,
+    pub max_expired: Option<u32>,
+    pub max_disputed: Option<u32>,
+}
+
+impl ReputationRequirement {
+    pub fn is_met(&self, stats: &ReputationStats) -> bool {
+        stats.delivered >= self.min_delivered
+            && self.max_expired.is_none_or(|max| stats.expired <= max)
+            && self.max_disputed.is_none_or(|max| stats.disputed <= max)
+    }
+}
+
+#[cw_serde]
+pub enum IcaStatus {
+    /// Channel handshake is in progress
+    Registering,
+    Open,
+    /// Channel got closed after a timeout and the account has to be registered again
+    Closed,
+}
+
+#[cw_serde]
+pub struct InterchainAccount {
+    /// Remote address, kept when the channel is closed since re-registering on the same port restores it
+    pub address: Option<String>,
+    pub connection_id: String,
+    pub status: IcaStatus,
+}
+
+#[cw_serde]
+pub enum QueryKind {
+    /// TX query for incoming transfers to the interchain account
+    Transfers,
+    /// KV query for the balance of the interchain account in a denom
+    Balance { denom: String },
+}
+
+/// Query deposit paid in advance by the creator or a sponsor of a proposal
+#[cw_serde]
+pub struct QueryDeposit {
+    pub payer: Addr,
+    pub amount: Coin,
+}
+
+/// Interchain query registered by the contract on behalf of a proposal
+#[cw_serde]
+pub struct InterchainQueryInfo {
+    pub port_id: String,
+    pub kind: QueryKind,
+    pub update_period: u64,
+    pub deposit: Option<QueryDeposit>,
+}
+
+/// Balance of an interchain account reported by a KV interchain query
+#[cw_serde]
+pub struct RemoteBalance {
+    pub amount: Uint128,
+    pub last_submitted_local_height: u64,
 }
 
 #[cw_serde]
@@ -67,38 +506,236 @@ pub struct Transfer {
     pub amount: String,
 }
 
+/// Why a deposit to an interchain account wasn't credited to its proposal
+#[cw_serde]
+pub enum QuarantineReason {
+    /// Sender isn't a bech32 address the funds could be attributed to
+    UnknownSender,
+    /// Proposal of the account is completed and doesn't take funds anymore
+    ProposalClosed,
+    /// No balance query tracks the denom and nobody paid for a new one
+    UntrackedDenom,
+    /// Recipient isn't an interchain account of the contract, so there is no account to refund from
+    UnknownAccount,
+}
+
+/// Deposit that reached an interchain account but couldn't be credited to its proposal
+#[cw_serde]
+pub struct QuarantinedDeposit {
+    pub sender: String,
+    pub recipient: String,
+    pub denom: String,
+    pub amount: Uint128,
+    pub tx_hash: String,
+    /// Port of the interchain account holding the deposit, refunds are sent from it.
+    /// Empty when the recipient isn't a known account
+    pub port_id: String,
+    pub reason: QuarantineReason,
+    pub refund_pending: bool,
+}
+
+pub static CONFIG: Item<Config> = Item::new("config");
+
 pub static PROPOSAL_INDEX: Item<u64> = Item::new("proposal_index");
 pub static PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
 pub static PROPOSAL_FUNDING: Map<(u64, &str), ProjectFunding> = Map::new("project_funding");
 
 pub static APPLICATIONS: Map<(u64, Addr), Application> = Map::new("applications");
+// (participant, proposal id, application sender) -> roles of the participant in the application
+pub static PARTICIPANT_APPLICATIONS: Map<(&Addr, u64, &Addr), Vec<ParticipantRole>> =
+    Map::new("participant_applications");
 pub static APPLICATION_FUNDING: Map<(u64, Addr, &str), Uint128> = Map::new("application_funding");
+// (proposal id, application sender, funder) -> whether the funds came from a remote chain
+pub static APPLICATION_BACKERS: Map<(u64, &Addr, &Addr), bool> = Map::new("application_backers");
+// (proposal id, application sender, funder) -> funds of the funder paid out to the application
+pub static APPLICATION_CONTRIBUTIONS: Map<(u64, &Addr, &Addr), Vec<Coin>> = Map::new("application_contributions");
+
+pub static AUDITORS: Map<&Addr, AuditorProfile> = Map::new("auditors");
+pub static REPUTATION: Map<&Addr, ReputationStats> = Map::new("reputation");
+
+/// Maximum number of applications competing in a selection
+pub const MAX_SELECTION_APPLICATIONS: usize = 50;
+
+pub const EXTENSION_VOTING_PERIOD: u64 = 50_000;
+// (proposal id, application sender) -> open extension request
+pub static EXTENSION_REQUESTS: Map<(u64, &Addr), ExtensionRequest> = Map::new("extension_requests");
+// (proposal id, application sender, funder) -> vote for the open request
+pub static EXTENSION_VOTES: Map<(u64, &Addr, &Addr), bool> = Map::new("extension_votes");
+/// Maximum length of the summary of an update and of an auditor comment
+pub const MAX_UPDATE_TEXT_LENGTH: usize = 2_000;
+// (proposal id, application sender, update id) -> progress update, ids are consecutive from 0
+pub static UPDATES: Map<(u64, &Addr, u64), ProgressUpdate> = Map::new("updates");
+
+pub static DISPUTE_INDEX: Item<u64> = Item::new("dispute_index");
+pub static DISPUTES: Map<u64, Dispute> = Map::new("disputes");
+// (proposal id, application sender, auditor) -> open or upheld dispute about the verification of the auditor
+pub static AUDITOR_DISPUTES: Map<(u64, &Addr, &Addr), u64> = Map::new("auditor_disputes");
+
+// funds held by the contract or its interchain accounts, deposits of a denom on different accounts are kept apart
+pub static CUSTODY_FUNDS: IndexedMap<CustodyKey, CustodyFunds, CustodyIndexes> = IndexedMap::new(
+    "proposal_custody",
+    CustodyIndexes {
+        proposal: MultiIndex::new(custody_proposal_idx, "proposal_custody", "proposal_custody__proposal"),
+        application: MultiIndex::new(
+            custody_application_idx,
+            "proposal_custody",
+            "proposal_custody__application",
+        ),
+        remote: MultiIndex::new(custody_remote_idx, "proposal_custody", "proposal_custody__remote"),
+    },
+);
 
-pub static CUSTODY_FUNDS: Map<(&Addr, &str), CustodyFunds> = Map::new("custody_funds");
+// (funder, denom) -> custody funds stored before the proposal was part of the key, emptied by the migration
+const LEGACY_CUSTODY_FUNDS: Map<(&Addr, &str), CustodyFunds> = Map::new("custody_funds");
+const LEGACY_CUSTODY_NAMESPACES: [&str; 5] = [
+    "custody_funds",
+    "custody_funds__proposal",
+    "custody_funds__application",
+    "custody_funds__locked",
+    "custody_funds__remote",
+];
 
 pub static LAST_CHECKED_BLOCKS: Map<(u64, &str), u64> = Map::new("last_checked_blocks");
 
-pub static INTERCHAIN_ACCOUNTS: Map<String, Option<(String, String)>> =
-    Map::new("interchain_accounts");
+pub static INTERCHAIN_ACCOUNTS: Map<String, InterchainAccount> = Map::new("interchain_accounts");
 
 pub static ADDRESS_TO_PROPOSAL: Map<String, u64> = Map::new("address_to_proposal");
+pub static ADDRESS_TO_PORT: Map<String, String> = Map::new("address_to_port");
+
+// (proposal id, connection id) -> port id of the interchain account
+pub static PROPOSAL_ICAS: Map<(u64, &str), String> = Map::new("proposal_icas");
+
+// connection id -> chain id of the counterparty chain, maintained by the admin
+pub static CONNECTION_CHAINS: Map<&str, String> = Map::new("connection_chains");
 
 pub static PROCESSED_TXS: Map<u64, bool> = Map::new("processed_tx");
 
+pub static QUARANTINE_INDEX: Item<u64> = Item::new("quarantine_index");
+pub static QUARANTINED_DEPOSITS: Map<u64, QuarantinedDeposit> = Map::new("quarantined_deposits");
+
 pub const DEFAULT_UPDATE_PERIOD: u64 = 6u64;
+pub const DEFAULT_QUERY_DEPOSIT: u128 = 1_000_000;
+// a new interchain account pays for its transfers query and the balance query of the first denom
+pub const REGISTRATION_QUERY_CREDITS: usize = 2;
+// registration fees in untrn collected by the contract and not withdrawn by the admin yet
+pub static REGISTRATION_FEES: Item<Uint128> = Item::new("registration_fees");
+
+pub const REGISTER_QUERY_REPLY_ID: u64 = 2;
+// every interchain transaction submessage gets its own reply id starting from this one
+pub const SUDO_PAYLOAD_REPLY_ID_START: u64 = 1_000;
 
-pub const SUDO_PAYLOAD_REPLY_ID: u64 = 1;
+// Default timeout for SubmitTX is two weeks
+pub const DEFAULT_TIMEOUT_SECONDS: u64 = 60 * 60 * 24 * 7 * 2;
 
-pub const REPLY_ID_STORAGE: Item<Vec<u8>> = Item::new("reply_queue_id");
+pub static OUTBOX_INDEX: Item<u64> = Item::new("outbox_index");
+pub static OUTBOX: Map<u64, PendingOperation> = Map::new("outbox");
+// delay in blocks before the first retry, doubled with every failed attempt
+pub const RETRY_BASE_DELAY: u64 = 100;
+pub const RETRY_MAX_DELAY: u64 = 100_000;
+pub const RETRY_SCHEDULE_NAME: &str = "crossfund-retries";
+pub const RETRY_BATCH_LIMIT: u32 = 10;
+// operations failing this many times are dropped from the outbox and reported in the errors queue
+pub const MAX_RETRY_ATTEMPTS: u32 = 10;
+
+pub const NEXT_REPLY_ID: Item<u64> = Item::new("next_reply_id");
+// reply id -> payload of the interchain transaction waiting for its channel and sequence
+pub const REPLY_PAYLOADS: Map<u64, Vec<u8>> = Map::new("reply_payloads");
 pub const SUDO_PAYLOAD: Map<(String, u64), Vec<u8>> = Map::new("sudo_payload");
 
-//pub const proce
+// fee denom put aside by anyone to pay relayers of interchain transactions
+pub static FEE_RESERVE: Item<Uint128> = Item::new("fee_reserve");
+// (channel id, sequence) -> fee of the interchain transaction
+pub static IBC_FEES: Map<(String, u64), IbcFeeRecord> = Map::new("ibc_fees");
+
+// queries waiting for their ids in reply, replies come in the same order as submessages
+pub const PENDING_QUERIES: Item<Vec<(u64, InterchainQueryInfo)>> = Item::new("pending_queries");
+// (proposal id, query id) -> query info
+pub static PROPOSAL_QUERIES: Map<(u64, u64), InterchainQueryInfo> = Map::new("proposal_queries");
+pub static QUERY_TO_PROPOSAL: Map<u64, u64> = Map::new("query_to_proposal");
+// port id -> prepaid query deposits that haven't been used by a query yet
+pub static QUERY_CREDITS: Map<&str, Vec<QueryDeposit>> = Map::new("query_credits");
+// (port id, denom) -> query id
+pub static BALANCE_QUERY_IDS: Map<(&str, &str), u64> = Map::new("balance_query_ids");
+pub static REMOTE_BALANCES: Map<(&str, &str), RemoteBalance> = Map::new("remote_balances");
 
 // interchain transaction responses - ack/err/timeout state to query later
 pub const ACKNOWLEDGEMENT_RESULTS: Map<(String, u64), AcknowledgementResult> =
     Map::new("acknowledgement_results");
 
-pub const ERRORS_QUEUE: Map<u32, String> = Map::new("errors_queue");
+pub const ERRORS_INDEX: Item<u32> = Item::new("errors_index");
+pub static ERRORS_QUEUE: IndexedMap<u32, ErrorRecord, ErrorIndexes> = IndexedMap::new(
+    "errors_queue",
+    ErrorIndexes {
+        kind: MultiIndex::new(error_kind_idx, "errors_queue", "errors_queue__kind"),
+    },
+);
+
+#[cw_serde]
+pub enum ErrorKind {
+    /// Sudo callback for a packet without a stored payload
+    MissingPayload,
+    /// Reopened interchain account got a different remote address
+    AddressMismatch,
+    /// No prepaid deposit to register an interchain query
+    MissingQueryDeposit,
+    /// Query result for a query the contract doesn't know about
+    UnknownQuery,
+    /// Interchain operation failed `MAX_RETRY_ATTEMPTS` times and was dropped from the outbox
+    RetriesExhausted,
+    /// Plain message stored before errors were recorded with a kind
+    Legacy,
+}
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::MissingPayload => "missing_payload",
+            ErrorKind::AddressMismatch => "address_mismatch",
+            ErrorKind::MissingQueryDeposit => "missing_query_deposit",
+            ErrorKind::UnknownQuery => "unknown_query",
+            ErrorKind::RetriesExhausted => "retries_exhausted",
+            ErrorKind::Legacy => "legacy",
+        }
+    }
+}
+
+#[cw_serde]
+pub struct ErrorRecord {
+    pub kind: ErrorKind,
+    pub proposal_id: Option<u64>,
+    pub channel_id: Option<String>,
+    pub sequence: Option<u64>,
+    pub height: u64,
+    pub details: String,
+}
+
+impl ErrorRecord {
+    pub fn new(kind: ErrorKind, height: u64, details: String) -> Self {
+        ErrorRecord {
+            kind,
+            proposal_id: None,
+            channel_id: None,
+            sequence: None,
+            height,
+            details,
+        }
+    }
+}
+
+pub struct ErrorIndexes<'a> {
+    pub kind: MultiIndex<'a, String, ErrorRecord, u32>,
+}
+
+impl<'a> IndexList<ErrorRecord> for ErrorIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ErrorRecord>> + '_> {
+        let v: Vec<&dyn Index<ErrorRecord>> = vec![&self.kind];
+        Box::new(v.into_iter())
+    }
+}
+
+fn error_kind_idx(_pk: &[u8], error: &ErrorRecord) -> String {
+    error.kind.name().to_string()
+}
 
 #[cw_serde]
 pub enum AcknowledgementResult {
@@ -110,30 +747,199 @@ pub enum AcknowledgementResult {
     Timeout(String),
 }
 
-pub fn save_reply_payload(store: &mut dyn Storage, payload: SudoPayload) -> StdResult<()> {
-    REPLY_ID_STORAGE.save(store, &to_vec(&payload)?)
+pub fn next_operation_id(store: &mut dyn Storage) -> StdResult<u64> {
+    let id = OUTBOX_INDEX.may_load(store)?.unwrap_or_default();
+    OUTBOX_INDEX.save(store, &(id + 1))?;
+    Ok(id)
+}
+
+/// Stores the payload under a fresh reply id that should be used for the submessage
+pub fn save_reply_payload(store: &mut dyn Storage, payload: SudoPayload) -> StdResult<u64> {
+    let id = NEXT_REPLY_ID
+        .may_load(store)?
+        .unwrap_or(SUDO_PAYLOAD_REPLY_ID_START);
+    NEXT_REPLY_ID.save(store, &(id + 1))?;
+    REPLY_PAYLOADS.save(store, id, &to_vec(&payload)?)?;
+    Ok(id)
 }
 
-pub fn read_reply_payload(store: &mut dyn Storage) -> StdResult<SudoPayload> {
-    let data = REPLY_ID_STORAGE.load(store)?;
+pub fn read_reply_payload(store: &mut dyn Storage, reply_id: u64) -> StdResult<SudoPayload> {
+    let data = REPLY_PAYLOADS.load(store, reply_id)?;
+    REPLY_PAYLOADS.remove(store, reply_id);
     from_binary(&Binary(data))
 }
 
-pub fn add_error_to_queue(store: &mut dyn Storage, error_msg: String) -> Option<()> {
-    let result = ERRORS_QUEUE
-        .keys(store, None, None, Order::Descending)
-        .next()
-        .and_then(|data| data.ok())
-        .map(|c| c + 1)
-        .or(Some(0));
+pub fn add_error_to_queue(store: &mut dyn Storage, error: ErrorRecord) -> StdResult<u32> {
+    // ids keep growing after the queue is cleared
+    let idx = ERRORS_INDEX.may_load(store)?.unwrap_or_default();
+    ERRORS_INDEX.save(store, &(idx + 1))?;
+    ERRORS_QUEUE.save(store, idx, &error)?;
+    Ok(idx)
+}
+
+/// Saves the errors again so that the kind index covers the ones stored before it existed,
+/// plain messages of the old queue become `ErrorKind::Legacy` records
+pub fn reindex_errors(store: &mut dyn Storage) -> StdResult<usize> {
+    // raw keys, the values of the old queue don't load as records
+    let ids = ERRORS_QUEUE
+        .keys_raw(store, None, None, Order::Ascending)
+        .map(u32::from_vec)
+        .collect::<StdResult<Vec<_>>>()?;
+    for id in ids.iter() {
+        let key = ERRORS_QUEUE.key(*id);
+        let raw = store
+            .get(&key)
+            .ok_or_else(|| StdError::not_found("ErrorRecord"))?;
+        let error = match from_slice::<ErrorRecord>(&raw) {
+            Ok(error) => error,
+            Err(_) => {
+                // the old value can't be loaded to clear its indexes, it never had any
+                store.remove(&key);
+                ErrorRecord::new(ErrorKind::Legacy, 0, from_slice::<String>(&raw)?)
+            }
+        };
+        ERRORS_QUEUE.save(store, *id, &error)?;
+    }
+    // the old queue had no counter and took the id after the last error
+    if let Some(last) = ids.last() {
+        let next = ERRORS_INDEX.may_load(store)?.unwrap_or_default().max(last + 1);
+        ERRORS_INDEX.save(store, &next)?;
+    }
+    Ok(ids.len())
+}
+
+/// Converts the shares of applications stored in whole percents to basis points
+/// and records their participants, which weren't tracked at the time
+pub fn migrate_applications(store: &mut dyn Storage) -> StdResult<usize> {
+    // raw keys, the values of legacy applications don't load
+    let keys = APPLICATIONS
+        .keys_raw(store, None, None, Order::Ascending)
+        .map(<(u64, Addr)>::from_vec)
+        .collect::<StdResult<Vec<_>>>()?;
 
-    result.and_then(|idx| ERRORS_QUEUE.save(store, idx, &error_msg).ok())
+    let mut migrated = 0;
+    for (proposal_id, sender) in keys {
+        let raw = store
+            .get(&APPLICATIONS.key((proposal_id, sender.clone())))
+            .ok_or_else(|| StdError::not_found("Application"))?;
+        if from_slice::<Application>(&raw).is_ok() {
+            continue;
+        }
+        let application = Application::from(from_slice::<LegacyApplication>(&raw)?);
+        APPLICATIONS.save(store, (proposal_id, sender.clone()), &application)?;
+        save_participant_roles(store, proposal_id, &sender, &application)?;
+        migrated += 1;
+    }
+    Ok(migrated)
+}
+
+pub fn save_participant_roles(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+    application_sender: &Addr,
+    application: &Application,
+) -> StdResult<()> {
+    let roles = application.applicants.iter().map(|gf| (gf, ParticipantRole::Applicant))
+        .chain(application.auditors.iter().map(|gf| (gf, ParticipantRole::Auditor)));
+
+    for (gf, role) in roles {
+        PARTICIPANT_APPLICATIONS.update(store, (&gf.recipient, proposal_id, application_sender), |roles| -> StdResult<_> {
+            let mut roles = roles.unwrap_or_default();
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
+            Ok(roles)
+        })?;
+    }
+    Ok(())
+}
+
+/// Moves the custody funds stored under (funder, denom) to the key with the proposal and drops the old indexes
+pub fn migrate_custody_funds(store: &mut dyn Storage) -> StdResult<usize> {
+    let legacy = LEGACY_CUSTODY_FUNDS
+        .range(store, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for ((funder, denom), funds) in legacy.iter() {
+        // remote funders were kept under their address on the remote chain, they withdraw with the one on Neutron
+        let funder = if is_remote_recipient(funder) {
+            local_address(funder.as_str())?
+        } else {
+            funder.clone()
+        };
+        CUSTODY_FUNDS.save(store, custody_key(&funder, funds.proposal_id, denom, funds.remote.as_deref()), funds)?;
+    }
+
+    for namespace in LEGACY_CUSTODY_NAMESPACES {
+        let mut start = (namespace.len() as u16).to_be_bytes().to_vec();
+        start.extend_from_slice(namespace.as_bytes());
+        let mut end = start.clone();
+        // namespaces end with an ascii letter, the next byte value bounds all of their keys
+        if let Some(last) = end.last_mut() {
+            *last += 1;
+        }
+        let keys: Vec<Vec<u8>> = store
+            .range(Some(&start), Some(&end), Order::Ascending)
+            .map(|(key, _)| key)
+            .collect();
+        for key in keys {
+            store.remove(&key);
+        }
+    }
+
+    Ok(legacy.len())
+}
+
+pub fn read_errors_from_queue(
+    store: &dyn Storage,
+    start_after: Option<u32>,
+    limit: usize,
+    kind: Option<ErrorKind>,
+) -> StdResult<Vec<(u32, ErrorRecord)>> {
+    let start = start_after.map(Bound::exclusive);
+    match kind {
+        Some(kind) => ERRORS_QUEUE
+            .idx
+            .kind
+            .prefix(kind.name().to_string())
+            .range(store, start, None, Order::Ascending)
+            .take(limit)
+            .collect(),
+        None => ERRORS_QUEUE
+            .range(store, start, None, Order::Ascending)
+            .take(limit)
+            .collect(),
+    }
+}
+
+pub fn quarantine_deposit(
+    store: &mut dyn Storage,
+    deposit: QuarantinedDeposit,
+) -> StdResult<u64> {
+    let index = QUARANTINE_INDEX.may_load(store)?.unwrap_or_default();
+    QUARANTINED_DEPOSITS.save(store, index, &deposit)?;
+    QUARANTINE_INDEX.save(store, &(index + 1))?;
+    Ok(index)
 }
 
-pub fn read_errors_from_queue(store: &dyn Storage) -> StdResult<Vec<(Vec<u8>, String)>> {
-    ERRORS_QUEUE
-        .range_raw(store, None, None, Order::Ascending)
-        .collect()
+/// Takes the oldest prepaid deposit of the interchain account for a new query.
+/// Returns `None` when a deposit is required but nobody has paid for it
+pub fn take_query_credit(
+    store: &mut dyn Storage,
+    port_id: &str,
+) -> StdResult<Option<Option<QueryDeposit>>> {
+    if CONFIG.load(store)?.query_deposit.is_zero() {
+        return Ok(Some(None));
+    }
+
+    let mut credits = QUERY_CREDITS.may_load(store, port_id)?.unwrap_or_default();
+    if credits.is_empty() {
+        return Ok(None);
+    }
+
+    let credit = credits.remove(0);
+    QUERY_CREDITS.save(store, port_id, &credits)?;
+    Ok(Some(Some(credit)))
 }
 
 pub fn read_sudo_payload(
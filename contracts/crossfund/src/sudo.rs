@@ -4,27 +4,30 @@ use cosmos_sdk_proto::cosmos::{
 };
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    Addr, Binary, DepsMut, Env, Event, Reply, Response, StdError, StdResult, Storage, Uint128,
+    coins, BankMsg, Binary, Coin, CosmosMsg, DepsMut, Env, Event, Reply, Response, StdError, StdResult, Storage,
+    SubMsg, Uint128,
 };
 use neutron_sdk::{
     bindings::{
-        msg::MsgSubmitTxResponse,
+        msg::{MsgRegisterInterchainQueryResponse, MsgSubmitTxResponse, NeutronMsg},
         query::{NeutronQuery, QueryRegisteredQueryResponse},
-        types::Height,
+        types::{encode_hex, Height},
     },
     interchain_queries::{
         get_registered_query,
         types::{TransactionFilterItem, TransactionFilterOp, TransactionFilterValue},
         v045::{
-            new_register_transfers_query_msg,
+            new_register_balance_query_msg, new_register_transfers_query_msg,
+            queries::query_balance,
             types::{COSMOS_SDK_TRANSFER_MSG_URL, RECIPIENT_FIELD},
         },
     },
-    interchain_txs::helpers::{get_port_id, get_proposal_id},
+    interchain_txs::helpers::{decode_acknowledgement_response, get_proposal_id},
     sudo::msg::RequestPacket,
     NeutronError, NeutronResult,
 };
 use prost::Message;
+use sha2::{Digest, Sha256};
 
 const MAX_ALLOWED_MESSAGES: usize = 20;
 const MAX_ALLOWED_TRANSFER: u128 = u128::MAX;
@@ -32,12 +35,14 @@ const MAX_ALLOWED_TRANSFER: u128 = u128::MAX;
 use crate::{
     msg::NeutronResponse,
     storage::{
-        add_error_to_queue, read_reply_payload, read_sudo_payload, save_sudo_payload,
-        AcknowledgementResult, CustodyFunds, Transfer, ACKNOWLEDGEMENT_RESULTS,
-        ADDRESS_TO_PROPOSAL, CUSTODY_FUNDS, DEFAULT_UPDATE_PERIOD, INTERCHAIN_ACCOUNTS,
-        PROCESSED_TXS, PROPOSAL_FUNDING,
+        add_error_to_queue, quarantine_deposit, take_query_credit, read_reply_payload, read_sudo_payload,
+        save_sudo_payload, AcknowledgementResult, IbcFeeRecord, ErrorKind, ErrorRecord, SudoPayload, FEE_RESERVE, IBC_FEES, OUTBOX, IcaOperation, IcaStatus, InterchainAccount, InterchainQueryInfo,
+        QuarantinedDeposit, QuarantineReason, QueryKind, ProposalStatus, CONFIG, PROPOSALS, QUERY_CREDITS, RemoteBalance, Transfer, ACKNOWLEDGEMENT_RESULTS,
+        custody_key, ADDRESS_TO_PORT, ADDRESS_TO_PROPOSAL, BALANCE_QUERY_IDS, CUSTODY_FUNDS, DEFAULT_UPDATE_PERIOD,
+        INTERCHAIN_ACCOUNTS, PENDING_QUERIES, PROCESSED_TXS, PROPOSAL_FUNDING, PROPOSAL_QUERIES,
+        QUARANTINED_DEPOSITS, QUERY_TO_PROPOSAL, REGISTER_QUERY_REPLY_ID, REMOTE_BALANCES, MAX_RETRY_ATTEMPTS,
     },
-    utils::hash_data,
+    utils::{add_custody, crossfund_event, fee_denom_amount, funded_event, hash_data, local_address, lock_pool, retry_delay, total_ibc_fee, FEE_DENOM},
 };
 
 #[cw_serde]
@@ -63,38 +68,157 @@ pub fn sudo_open_ack(
 
     // Update the storage record associated with the interchain account.
     if let Ok(parsed_version) = parsed_version {
+        let previous = INTERCHAIN_ACCOUNTS.may_load(store, port_id.clone())?;
+
         INTERCHAIN_ACCOUNTS.save(
             store,
             port_id.clone(),
-            &Some((
-                parsed_version.address.clone(),
-                parsed_version.controller_connection_id.clone(),
-            )),
-        )?;
-        ADDRESS_TO_PROPOSAL.save(
-            store,
-            parsed_version.address.clone(),
-            &get_proposal_id(port_id.as_str()),
+            &InterchainAccount {
+                address: Some(parsed_version.address.clone()),
+                connection_id: parsed_version.controller_connection_id.clone(),
+                status: IcaStatus::Open,
+            },
         )?;
 
-        //return Ok(Response::default());
+        let event = crossfund_event("ica-opened")
+            .add_attribute("proposal_id", get_proposal_id(port_id.as_str()).to_string())
+            .add_attribute("port_id", port_id.as_str())
+            .add_attribute("address", parsed_version.address.as_str());
+
+        // a reopened channel keeps the address, custody mappings and queries it had before
+        if let Some(InterchainAccount {
+            address: Some(address),
+            ..
+        }) = previous
+        {
+            if address != parsed_version.address {
+                add_error_to_queue(
+                    store,
+                    ErrorRecord {
+                        proposal_id: Some(get_proposal_id(port_id.as_str())),
+                        ..ErrorRecord::new(
+                            ErrorKind::AddressMismatch,
+                            env.block.height,
+                            format!(
+                                "Reopened {} with address {} instead of {}",
+                                port_id, parsed_version.address, address
+                            ),
+                        )
+                    },
+                )?;
+            }
+            return Ok(Response::default().add_event(event.add_attribute("reopened", "true")));
+        }
+
+        let event = event.add_attribute("reopened", "false");
+
+        let proposal_id = get_proposal_id(port_id.as_str());
+
+        ADDRESS_TO_PROPOSAL.save(store, parsed_version.address.clone(), &proposal_id)?;
+        ADDRESS_TO_PORT.save(store, parsed_version.address.clone(), &port_id)?;
+
+        let deposit = match take_query_credit(store, &port_id)? {
+            Some(deposit) => deposit,
+            None => {
+                add_error_to_queue(
+                    store,
+                    ErrorRecord {
+                        proposal_id: Some(proposal_id),
+                        ..ErrorRecord::new(
+                            ErrorKind::MissingQueryDeposit,
+                            env.block.height,
+                            format!("No query deposit paid for {}", port_id),
+                        )
+                    },
+                )?;
+                return Ok(Response::default().add_event(event));
+            }
+        };
+
+        // interchain queries are registered over the connection on our side
+        let msg = new_register_transfers_query_msg(
+            parsed_version.controller_connection_id,
+            parsed_version.address,
+            DEFAULT_UPDATE_PERIOD,
+            Some(env.block.height),
+        )?;
 
-        return Ok(
-            Response::new().add_message(new_register_transfers_query_msg(
-                parsed_version.host_connection_id,
-                parsed_version.address,
-                DEFAULT_UPDATE_PERIOD,
-                Some(env.block.height),
-            )?),
-        );
+        return Ok(Response::new().add_event(event).add_submessage(register_query(
+            store,
+            proposal_id,
+            InterchainQueryInfo {
+                port_id,
+                kind: QueryKind::Transfers,
+                update_period: DEFAULT_UPDATE_PERIOD,
+                deposit,
+            },
+            msg,
+        )?));
     }
     Err(NeutronError::Std(StdError::generic_err(
         "Can't parse counterparty_version",
     )))
 }
 
+pub fn sudo_response(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    request: RequestPacket,
+    data: Binary,
+) -> NeutronResponse {
+    deps.api
+        .debug(format!("WASMDEBUG: sudo response: {:?}", request).as_str());
+
+    let seq_id = request
+        .sequence
+        .ok_or_else(|| StdError::generic_err("sequence not found"))?;
+
+    let channel_id = request
+        .source_channel
+        .ok_or_else(|| StdError::generic_err("channel_id not found"))?;
+
+    let fee_refund = settle_ibc_fee(deps.storage, &channel_id, seq_id, false)?;
+
+    let payload = read_sudo_payload(deps.storage, channel_id.clone(), seq_id).ok();
+
+    if let Some(payload) = payload {
+        let item_types = decode_acknowledgement_response(data)?
+            .into_iter()
+            .map(|item| item.msg_type)
+            .collect::<Vec<String>>();
+
+        // update but also check that we don't update same seq_id twice
+        ACKNOWLEDGEMENT_RESULTS.update(
+            deps.storage,
+            (payload.port_id.clone(), seq_id),
+            |maybe_ack| -> StdResult<AcknowledgementResult> {
+                match maybe_ack {
+                    Some(_ack) => Err(StdError::generic_err("trying to update same seq_id")),
+                    None => Ok(AcknowledgementResult::Success(item_types)),
+                }
+            },
+        )?;
+
+        operation_succeeded(deps.storage, &payload)?;
+
+        return Ok(Response::default().add_messages(fee_refund).add_event(operation_event(
+            "operation-acknowledged",
+            &payload,
+            &channel_id,
+            seq_id,
+        )));
+    } else {
+        let error_msg = "WASMDEBUG: Error: Unable to read sudo payload";
+        deps.api.debug(error_msg);
+        add_error_to_queue(deps.storage, missing_payload_error(&env, channel_id, seq_id))?;
+    }
+
+    Ok(Response::default().add_messages(fee_refund))
+}
+
 pub fn sudo_error(
     deps: DepsMut<NeutronQuery>,
+    env: Env,
     request: RequestPacket,
     details: String,
 ) -> NeutronResponse {
@@ -110,61 +234,236 @@ pub fn sudo_error(
     let channel_id = request
         .source_channel
         .ok_or_else(|| StdError::generic_err("channel_id not found"))?;
-    let payload = read_sudo_payload(deps.storage, channel_id, seq_id).ok();
+
+    // an error is still an acknowledgement for the fee module
+    let fee_refund = settle_ibc_fee(deps.storage, &channel_id, seq_id, false)?;
+
+    let payload = read_sudo_payload(deps.storage, channel_id.clone(), seq_id).ok();
 
     if let Some(payload) = payload {
         // update but also check that we don't update same seq_id twice
         ACKNOWLEDGEMENT_RESULTS.update(
             deps.storage,
-            (payload.port_id, seq_id),
+            (payload.port_id.clone(), seq_id),
             |maybe_ack| -> StdResult<AcknowledgementResult> {
                 match maybe_ack {
                     Some(_ack) => Err(StdError::generic_err("trying to update same seq_id")),
-                    None => Ok(AcknowledgementResult::Error((payload.message, details))),
+                    None => Ok(AcknowledgementResult::Error((
+                        payload.message.clone(),
+                        details.clone(),
+                    ))),
                 }
             },
         )?;
+
+        let event = operation_event("operation-failed", &payload, &channel_id, seq_id)
+            .add_attribute("error", details.as_str());
+
+        operation_failed(deps.storage, &env, &payload, details)?;
+
+        return Ok(Response::default().add_messages(fee_refund).add_event(event));
     } else {
         let error_msg = "WASMDEBUG: Error: Unable to read sudo payload";
         deps.api.debug(error_msg);
-        add_error_to_queue(deps.storage, error_msg.to_string());
+        add_error_to_queue(deps.storage, missing_payload_error(&env, channel_id, seq_id))?;
     }
 
-    Ok(Response::default())
+    Ok(Response::default().add_messages(fee_refund))
 }
 
-pub fn sudo_timeout(store: &mut dyn Storage, request: RequestPacket) -> NeutronResponse {
+pub fn sudo_timeout(store: &mut dyn Storage, env: Env, request: RequestPacket) -> NeutronResponse {
     let seq_id = request
         .sequence
         .ok_or_else(|| StdError::generic_err("sequence not found"))?;
 
+    // ICA channels are ordered so a timeout closes the channel
+    if let Some(port_id) = &request.source_port {
+        close_interchain_account(store, port_id)?;
+    }
+
     let channel_id = request
         .source_channel
         .ok_or_else(|| StdError::generic_err("channel_id not found"))?;
 
-    let payload = read_sudo_payload(store, channel_id, seq_id).ok();
+    let fee_refund = settle_ibc_fee(store, &channel_id, seq_id, true)?;
+
+    let payload = read_sudo_payload(store, channel_id.clone(), seq_id).ok();
     if let Some(payload) = payload {
         // update but also check that we don't update same seq_id twice
         ACKNOWLEDGEMENT_RESULTS.update(
             store,
-            (payload.port_id, seq_id),
+            (payload.port_id.clone(), seq_id),
             |maybe_ack| -> StdResult<AcknowledgementResult> {
                 match maybe_ack {
                     Some(_ack) => Err(StdError::generic_err("trying to update same seq_id")),
-                    None => Ok(AcknowledgementResult::Timeout(payload.message)),
+                    None => Ok(AcknowledgementResult::Timeout(payload.message.clone())),
                 }
             },
         )?;
+
+        operation_failed(store, &env, &payload, "timeout".to_string())?;
+
+        return Ok(Response::default().add_messages(fee_refund).add_event(operation_event(
+            "operation-timeout",
+            &payload,
+            &channel_id,
+            seq_id,
+        )));
     } else {
-        let error_msg = "WASMDEBUG: Error: Unable to read sudo payload";
-        add_error_to_queue(store, error_msg.to_string());
+        add_error_to_queue(store, missing_payload_error(&env, channel_id, seq_id))?;
+    }
+
+    Ok(Response::default().add_messages(fee_refund))
+}
+
+fn operation_event(action: &str, payload: &SudoPayload, channel_id: &str, seq_id: u64) -> Event {
+    crossfund_event(action)
+        .add_attribute("outbox_id", payload.outbox_id.to_string())
+        .add_attribute("operation", payload.operation.name())
+        .add_attribute("port_id", payload.port_id.as_str())
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("sequence", seq_id.to_string())
+}
+
+fn missing_payload_error(env: &Env, channel_id: String, seq_id: u64) -> ErrorRecord {
+    ErrorRecord {
+        channel_id: Some(channel_id),
+        sequence: Some(seq_id),
+        ..ErrorRecord::new(
+            ErrorKind::MissingPayload,
+            env.block.height,
+            "Unable to read sudo payload".to_string(),
+        )
+    }
+}
+
+/// The relayer is paid only the fee of the packet it delivered (ack or timeout),
+/// the rest is returned by the fee module to the contract. It's split between the caller
+/// and the fee reserve in proportion to what each of them paid
+fn settle_ibc_fee(
+    store: &mut dyn Storage,
+    channel_id: &str,
+    seq_id: u64,
+    timed_out: bool,
+) -> StdResult<Vec<CosmosMsg<NeutronMsg>>> {
+    let key = (channel_id.to_string(), seq_id);
+    let mut record = match IBC_FEES.may_load(store, key.clone())? {
+        Some(record) if record.refunded.is_none() => record,
+        _ => return Ok(vec![]),
+    };
+
+    let mut refunded: Vec<Coin> = record.fee.recv_fee.clone();
+    if timed_out {
+        refunded.extend(record.fee.ack_fee.clone());
+    } else {
+        refunded.extend(record.fee.timeout_fee.clone());
+    }
+
+    let amount = fee_denom_amount(&refunded);
+    let mut msgs: Vec<CosmosMsg<NeutronMsg>> = vec![];
+    let mut to_reserve = amount;
+
+    if let Some(payment) = &record.payment {
+        let share = amount.multiply_ratio(payment.amount, total_ibc_fee(&record.fee));
+        if !share.is_zero() {
+            msgs.push(
+                BankMsg::Send {
+                    to_address: payment.payer.to_string(),
+                    amount: coins(share.u128(), FEE_DENOM),
+                }
+                .into(),
+            );
+            to_reserve -= share;
+        }
+    }
+
+    let reserve = FEE_RESERVE.may_load(store)?.unwrap_or_default();
+    FEE_RESERVE.save(store, &(reserve + to_reserve))?;
+
+    record.refunded = Some(refunded);
+    IBC_FEES.save(store, key, &record)?;
+    Ok(msgs)
+}
+
+fn close_interchain_account(store: &mut dyn Storage, port_id: &str) -> StdResult<()> {
+    if let Some(mut ica) = INTERCHAIN_ACCOUNTS.may_load(store, port_id.to_string())? {
+        ica.status = IcaStatus::Closed;
+        INTERCHAIN_ACCOUNTS.save(store, port_id.to_string(), &ica)?;
+    }
+    Ok(())
+}
+
+fn operation_succeeded(store: &mut dyn Storage, payload: &SudoPayload) -> StdResult<()> {
+    OUTBOX.remove(store, payload.outbox_id);
+
+    match &payload.operation {
+        IcaOperation::RefundQuarantined { id } => {
+            QUARANTINED_DEPOSITS.remove(store, *id);
+        }
+        // the funds left custody when the withdrawal was requested
+        IcaOperation::WithdrawCustody { .. } => {}
+    }
+    Ok(())
+}
+
+/// Failed operations stay in the outbox until they are retried with a backoff
+fn operation_failed(
+    store: &mut dyn Storage,
+    env: &Env,
+    payload: &SudoPayload,
+    error: String,
+) -> StdResult<()> {
+    let mut pending = match OUTBOX.may_load(store, payload.outbox_id)? {
+        Some(pending) => pending,
+        None => return Ok(()),
+    };
+
+    if pending.attempts >= MAX_RETRY_ATTEMPTS {
+        OUTBOX.remove(store, payload.outbox_id);
+        add_error_to_queue(
+            store,
+            ErrorRecord {
+                proposal_id: Some(get_proposal_id(&pending.port_id)),
+                ..ErrorRecord::new(
+                    ErrorKind::RetriesExhausted,
+                    env.block.height,
+                    format!(
+                        "Dropped {} operation {} after {} attempts: {}",
+                        pending.operation.name(),
+                        payload.outbox_id,
+                        pending.attempts,
+                        error
+                    ),
+                )
+            },
+        )?;
+        // the operation can be started again from scratch
+        match &pending.operation {
+            IcaOperation::RefundQuarantined { id } => {
+                QUARANTINED_DEPOSITS.update(store, *id, |deposit| -> StdResult<_> {
+                    let mut deposit = deposit.ok_or_else(|| StdError::not_found("Quarantined deposit"))?;
+                    deposit.refund_pending = false;
+                    Ok(deposit)
+                })?;
+            }
+            IcaOperation::WithdrawCustody { funder, proposal_id, denom, amount, .. } => {
+                CUSTODY_FUNDS.update(store, custody_key(funder, *proposal_id, denom, Some(&pending.port_id)), |funds| -> StdResult<_> {
+                    Ok(add_custody(funds, *amount, *proposal_id, Some(pending.port_id.clone())))
+                })?;
+            }
+        }
+        return Ok(());
     }
 
-    Ok(Response::default())
+    pending.in_flight = false;
+    pending.last_error = Some(error);
+    pending.retry_after = env.block.height + retry_delay(pending.attempts);
+    OUTBOX.save(store, payload.outbox_id, &pending)?;
+    Ok(())
 }
 
 pub fn prepare_sudo_payload(mut deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
-    let payload = read_reply_payload(deps.storage)?;
+    let payload = read_reply_payload(deps.storage, msg.id)?;
     let resp: MsgSubmitTxResponse = serde_json_wasm::from_slice(
         msg.result
             .into_result()
@@ -178,13 +477,22 @@ pub fn prepare_sudo_payload(mut deps: DepsMut, _env: Env, msg: Reply) -> StdResu
         .debug(format!("WASMDEBUG: reply msg: {:?}", resp).as_str());
     let seq_id = resp.sequence_id;
     let channel_id = resp.channel;
+    IBC_FEES.save(
+        deps.storage,
+        (channel_id.clone(), seq_id),
+        &IbcFeeRecord {
+            fee: payload.fee.clone(),
+            refunded: None,
+            payment: payload.fee_payment.clone(),
+        },
+    )?;
     save_sudo_payload(deps.branch().storage, channel_id, seq_id, payload)?;
     Ok(Response::new())
 }
 
 pub fn sudo_tx_query_result(
     deps: DepsMut<NeutronQuery>,
-    env: Env,
+    _env: Env,
     query_id: u64,
     _height: Height,
     data: Binary,
@@ -194,6 +502,7 @@ pub fn sudo_tx_query_result(
     let body: TxBody = TxBody::decode(tx.body_bytes.as_slice())?;
 
     let digest = hash_data(&tx.body_bytes);
+    let tx_hash = encode_hex(&Sha256::digest(data.as_slice())).to_uppercase();
 
     if PROCESSED_TXS.has(deps.storage, digest) {
         return Ok(Response::default());
@@ -219,7 +528,8 @@ pub fn sudo_tx_query_result(
         _ => {
             // For transfer queries, query data looks like `[{"field:"transfer.recipient", "op":"eq", "value":"some_address"}]`
             let query_data: Vec<TransactionFilterItem> =
-                serde_json_wasm::from_str(transactions_filter.as_str()).unwrap();
+                serde_json_wasm::from_str(transactions_filter.as_str())
+                    .map_err(|e| NeutronError::SerdeJSONWasm(e.to_string()))?;
 
             let recipient = query_data
                 .iter()
@@ -241,69 +551,349 @@ pub fn sudo_tx_query_result(
 
             check_deposits_size(&deposits)?;
 
+            let mut balance_queries: Vec<SubMsg<NeutronMsg>> = vec![];
+            let mut events: Vec<Event> = Vec::with_capacity(deposits.len());
+
             for deposit in &deposits {
-                let load = ADDRESS_TO_PROPOSAL.may_load(deps.storage, deposit.recipient.clone())?;
+                let amount = deposit
+                    .amount
+                    .parse::<u128>()
+                    .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+                // only the addresses of the contract's own accounts are queried
+                let (proposal_id, port_id) = match (
+                    ADDRESS_TO_PROPOSAL.may_load(deps.storage, deposit.recipient.clone())?,
+                    ADDRESS_TO_PORT.may_load(deps.storage, deposit.recipient.clone())?,
+                ) {
+                    (Some(proposal_id), Some(port_id)) => (proposal_id, port_id),
+                    _ => {
+                        events.push(quarantine_transfer(
+                            deps.storage,
+                            deposit,
+                            &tx_hash,
+                            None,
+                            QuarantineReason::UnknownAccount,
+                        )?);
+                        continue;
+                    }
+                };
 
-                if load.is_none() {
-                    // TODO: Refund
+                if let Some(reason) = quarantine_reason(deps.storage, proposal_id, &port_id, deposit)? {
+                    events.push(quarantine_transfer(
+                        deps.storage,
+                        deposit,
+                        &tx_hash,
+                        Some((proposal_id, port_id)),
+                        reason,
+                    )?);
                     continue;
                 }
 
-                fund_proposal_remote(
+                let event = fund_proposal_remote(
                     deps.storage,
-                    env.clone(),
+                    port_id.clone(),
                     deposit.sender.as_str(),
                     &deposit.denom,
-                    Uint128::from(deposit.amount.parse::<u128>().unwrap()),
-                    load.unwrap(),
+                    Uint128::from(amount),
+                    proposal_id,
                     auto_agree,
                 )?;
+                events.push(event.add_attribute("tx_hash", tx_hash.as_str()));
+
+                if let Some(msg) =
+                    register_balance_query(deps.storage, proposal_id, &port_id, &deposit.denom)?
+                {
+                    balance_queries.push(msg);
+                }
             }
 
-            Ok(Response::default())
+            PROCESSED_TXS.save(deps.storage, digest, &true)?;
+
+            Ok(Response::default()
+                .add_events(events)
+                .add_submessages(balance_queries))
         }
     }
 }
 
+/// Checks whether a deposit to the account of the proposal can be credited to its sender
+/// Keeps track of a deposit that can't be credited so that it can be sent back with RefundQuarantined
+fn quarantine_transfer(
+    store: &mut dyn Storage,
+    deposit: &Transfer,
+    tx_hash: &str,
+    account: Option<(u64, String)>,
+    reason: QuarantineReason,
+) -> StdResult<Event> {
+    let amount = Uint128::from(
+        deposit
+            .amount
+            .parse::<u128>()
+            .map_err(|e| StdError::generic_err(e.to_string()))?,
+    );
+    let (proposal_id, port_id) = match account {
+        Some((proposal_id, port_id)) => (Some(proposal_id), port_id),
+        None => (None, String::new()),
+    };
+
+    let id = quarantine_deposit(
+        store,
+        QuarantinedDeposit {
+            sender: deposit.sender.clone(),
+            recipient: deposit.recipient.clone(),
+            denom: deposit.denom.clone(),
+            amount,
+            tx_hash: tx_hash.to_string(),
+            port_id,
+            reason: reason.clone(),
+            refund_pending: false,
+        },
+    )?;
+
+    let mut event = crossfund_event("deposit-quarantined").add_attribute("deposit_id", id.to_string());
+    if let Some(proposal_id) = proposal_id {
+        event = event.add_attribute("proposal_id", proposal_id.to_string());
+    }
+    Ok(event
+        .add_attribute("reason", format!("{:?}", reason))
+        .add_attribute("sender", deposit.sender.as_str())
+        .add_attribute("recipient", deposit.recipient.as_str())
+        .add_attribute("denom", deposit.denom.as_str())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("tx_hash", tx_hash))
+}
+
+fn quarantine_reason(
+    store: &dyn Storage,
+    proposal_id: u64,
+    port_id: &str,
+    deposit: &Transfer,
+) -> StdResult<Option<QuarantineReason>> {
+    if bech32::decode(&deposit.sender).is_err() {
+        return Ok(Some(QuarantineReason::UnknownSender));
+    }
+
+    let open = PROPOSALS
+        .may_load(store, proposal_id)?
+        .is_some_and(|p| p.status == ProposalStatus::Open);
+    if !open {
+        return Ok(Some(QuarantineReason::ProposalClosed));
+    }
+
+    let tracked = has_balance_query(store, port_id, &deposit.denom)?
+        || CONFIG.load(store)?.query_deposit.is_zero()
+        || !QUERY_CREDITS.may_load(store, port_id)?.unwrap_or_default().is_empty();
+    if !tracked {
+        return Ok(Some(QuarantineReason::UntrackedDenom));
+    }
+
+    Ok(None)
+}
+
 fn fund_proposal_remote(
     store: &mut dyn Storage,
-    env: Env,
+    port: String,
     sender: &str,
     denom: &str,
     amount: Uint128,
     proposal_id: u64,
     auto_agree: bool,
-) -> StdResult<()> {
+) -> StdResult<Event> {
     if amount == Uint128::zero() {
         return Err(StdError::generic_err("zero amount"));
     }
 
+    // the funder withdraws from Neutron, the funds are kept under the same account there
+    let funder = local_address(sender)?;
+
     let mut funding = PROPOSAL_FUNDING
         .load(store, (proposal_id, denom))
         .unwrap_or_default();
     funding.amount += amount;
     funding.auto_agree = auto_agree;
-    funding.sender = Addr::unchecked(sender);
+    funding.sender = funder.clone();
 
-    PROPOSAL_FUNDING.save(store, (proposal_id.clone(), sender.as_ref()), &funding)?;
+    PROPOSAL_FUNDING.save(store, (proposal_id, denom), &funding)?;
 
-    let port = get_port_id(
-        env.contract.address.as_str(),
-        &proposal_id.clone().to_string(),
-    );
+    let bounty_open = PROPOSALS.load(store, proposal_id)?.bounty_open();
+    CUSTODY_FUNDS.update(
+        store,
+        custody_key(&funder, proposal_id, denom, Some(&port)),
+        |funds| -> StdResult<_> {
+            let funds = add_custody(funds, amount, proposal_id, Some(port.clone()));
+            Ok(if bounty_open { lock_pool(funds) } else { funds })
+        },
+    )?;
+
+    Ok(
+        funded_event(proposal_id, funder.as_str(), denom, amount, auto_agree)
+            .add_attribute("source", "remote")
+            .add_attribute("sender", sender)
+            .add_attribute("port_id", port),
+    )
+}
+
+/// Dispatches a query registration and remembers who it belongs to until the id arrives in reply
+fn register_query(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+    info: InterchainQueryInfo,
+    msg: NeutronMsg,
+) -> StdResult<SubMsg<NeutronMsg>> {
+    let mut pending = PENDING_QUERIES.may_load(store)?.unwrap_or_default();
+    pending.push((proposal_id, info));
+    PENDING_QUERIES.save(store, &pending)?;
+
+    Ok(SubMsg::reply_on_success(msg, REGISTER_QUERY_REPLY_ID))
+}
+
+/// Registers a KV query for the balance of the interchain account in the given denom
+/// unless one is already registered or waiting for its id
+/// Whether the balance of the account in the denom is queried or the query is being registered
+fn has_balance_query(store: &dyn Storage, port_id: &str, denom: &str) -> StdResult<bool> {
+    if BALANCE_QUERY_IDS.has(store, (port_id, denom)) {
+        return Ok(true);
+    }
+
+    let kind = QueryKind::Balance {
+        denom: denom.to_string(),
+    };
+
+    Ok(PENDING_QUERIES
+        .may_load(store)?
+        .unwrap_or_default()
+        .iter()
+        .any(|(_, info)| info.port_id == port_id && info.kind == kind))
+}
+
+fn register_balance_query(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+    port_id: &str,
+    denom: &str,
+) -> NeutronResult<Option<SubMsg<NeutronMsg>>> {
+    if has_balance_query(store, port_id, denom)? {
+        return Ok(None);
+    }
 
-    CUSTODY_FUNDS.save(
+    let (address, connection_id) = match INTERCHAIN_ACCOUNTS.may_load(store, port_id.to_string())? {
+        Some(InterchainAccount {
+            address: Some(address),
+            connection_id,
+            ..
+        }) => (address, connection_id),
+        _ => return Ok(None),
+    };
+
+    let msg = new_register_balance_query_msg(
+        connection_id,
+        address,
+        denom.to_string(),
+        DEFAULT_UPDATE_PERIOD,
+    )?;
+
+    // deposits in denoms nobody paid a balance query for are quarantined before getting here
+    let deposit = take_query_credit(store, port_id)?.ok_or_else(|| {
+        StdError::generic_err(format!("No query deposit paid for the {} balance of {}", denom, port_id))
+    })?;
+
+    Ok(Some(register_query(
         store,
-        (&Addr::unchecked(sender), denom),
-        &CustodyFunds {
+        proposal_id,
+        InterchainQueryInfo {
+            port_id: port_id.to_string(),
+            kind: QueryKind::Balance {
+                denom: denom.to_string(),
+            },
+            update_period: DEFAULT_UPDATE_PERIOD,
+            deposit,
+        },
+        msg,
+    )?))
+}
+
+pub fn save_query_id(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    let resp: MsgRegisterInterchainQueryResponse = serde_json_wasm::from_slice(
+        msg.result
+            .into_result()
+            .map_err(StdError::generic_err)?
+            .data
+            .ok_or_else(|| StdError::generic_err("no result"))?
+            .as_slice(),
+    )
+    .map_err(|e| StdError::generic_err(format!("failed to parse response: {:?}", e)))?;
+
+    let mut pending = PENDING_QUERIES.load(deps.storage)?;
+    if pending.is_empty() {
+        return Err(StdError::generic_err("no pending interchain query"));
+    }
+    let (proposal_id, info) = pending.remove(0);
+    PENDING_QUERIES.save(deps.storage, &pending)?;
+
+    if let QueryKind::Balance { denom } = &info.kind {
+        BALANCE_QUERY_IDS.save(deps.storage, (info.port_id.as_str(), denom.as_str()), &resp.id)?;
+    }
+
+    PROPOSAL_QUERIES.save(deps.storage, (proposal_id, resp.id), &info)?;
+    QUERY_TO_PROPOSAL.save(deps.storage, resp.id, &proposal_id)?;
+
+    Ok(Response::new())
+}
+
+pub fn sudo_kv_query_result(
+    deps: DepsMut<NeutronQuery>,
+    env: Env,
+    query_id: u64,
+) -> NeutronResponse {
+    let info = match QUERY_TO_PROPOSAL.may_load(deps.storage, query_id)? {
+        Some(proposal_id) => PROPOSAL_QUERIES.may_load(deps.storage, (proposal_id, query_id))?,
+        None => None,
+    };
+
+    let (port_id, denom) = match info {
+        Some(InterchainQueryInfo {
+            port_id,
+            kind: QueryKind::Balance { denom },
+            ..
+        }) => (port_id, denom),
+        _ => {
+            let error_msg = format!("Unknown KV query id {}", query_id);
+            deps.api.debug(format!("WASMDEBUG: Error: {}", error_msg).as_str());
+            add_error_to_queue(
+                deps.storage,
+                ErrorRecord::new(ErrorKind::UnknownQuery, env.block.height, error_msg),
+            )?;
+            return Ok(Response::default());
+        }
+    };
+
+    let response = query_balance(deps.as_ref(), env, query_id)?;
+
+    let amount = response
+        .balances
+        .coins
+        .iter()
+        .find(|c| c.denom == denom)
+        .map(|c| c.amount)
+        .unwrap_or_default();
+
+    REMOTE_BALANCES.save(
+        deps.storage,
+        (port_id.as_str(), denom.as_str()),
+        &RemoteBalance {
             amount,
-            proposal_id,
-            locked: false,
-            remote: Some(port),
+            last_submitted_local_height: response.last_submitted_local_height,
         },
     )?;
 
-    Ok(())
+    Ok(Response::default().add_event(
+        crossfund_event("balance-updated")
+            .add_attribute("query_id", query_id.to_string())
+            .add_attribute("port_id", port_id)
+            .add_attribute("denom", denom)
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("height", response.last_submitted_local_height.to_string()),
+    ))
 }
 
 fn recipient_deposits_from_tx_body(
@@ -333,6 +923,7 @@ fn recipient_deposits_from_tx_body(
     Ok(deposits)
 }
 
+#[allow(clippy::absurd_extreme_comparisons)]
 fn check_deposits_size(deposits: &Vec<Transfer>) -> StdResult<()> {
     for deposit in deposits {
         match deposit.amount.parse::<u128>() {
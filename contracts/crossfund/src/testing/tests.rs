@@ -1,21 +1,47 @@
-use std::marker::PhantomData;
+use std::{cell::Cell, marker::PhantomData};
 
 use crate::{
     storage::{
         add_error_to_queue, 
         read_errors_from_queue, 
-        ERRORS_QUEUE, GoodFee
+        reindex_errors,
+        migrate_custody_funds,
+        ERRORS_QUEUE, ErrorKind, ParticipantRole, Application, ErrorRecord, GoodFee, QuarantinedDeposit, QuarantineReason, QUARANTINED_DEPOSITS, INTERCHAIN_ACCOUNTS,
+        custody_key, CUSTODY_FUNDS, CustodyFunds, REMOTE_BALANCES, RemoteBalance,
+        CONFIG, Config, ConflictRules, BondRules, AUDITORS, DisputeStatus, DISPUTES, ReputationStats, ReputationRequirement, ExtensionRequest, EXTENSION_VOTING_PERIOD, ProgressUpdate, APPLICATIONS, APPLICATION_BACKERS, APPLICATION_FUNDING, PROPOSALS, PROPOSAL_FUNDING, ProjectFunding, ProposalStatus, PROPOSAL_QUERIES, QueryKind, PENDING_QUERIES,
+        ADDRESS_TO_PORT, ADDRESS_TO_PROPOSAL, PROPOSAL_ICAS, IcaStatus, QUERY_CREDITS,
+        FEE_RESERVE, REGISTRATION_FEES, IBC_FEES, SUDO_PAYLOAD_REPLY_ID_START, save_reply_payload, read_sudo_payload, SudoPayload, IcaOperation, PendingOperation, OUTBOX, MAX_RETRY_ATTEMPTS, MAX_SELECTION_APPLICATIONS,
     }, 
-    query::{query_errors_queue}, execute::{submit_application, submit_proposal, fund_proposal_native, accept_application, approve_application}, msg::ApplicationSubmission,
+    query::{query_application, query_extension_request, query_updates, query_reputation, get_application_locked_funds, query_simulate_payout, query_applications, query_application_funding, query_applications_by_participant, query_errors_queue, query_all_proposals, query_proposal_summaries, query_pending_operations, query_quarantined_deposits, query_reconciliation, get_deposit_addresses}, execute::{submit_application, submit_proposal, fund_proposal_native, accept_application, approve_application, refund_quarantined, verify_application, update_query_period, register_ica, set_connection_chain, sponsor_queries, update_conflict_rules, withdraw_registration_fees, register_auditor, unbond_auditor, claim_auditor_bond, raise_dispute, resolve_dispute, expire_application, set_min_reputation, submit_deliverable, request_extension, vote_extension, withdraw_funds, open_selection, select_applications, open_bounty, post_update, comment_update, fund_fee_reserve, retry_pending, clear_errors}, msg::{MigrateMsg, ApplicationSubmission, ReconciliationResponse, AllProposalResponse, ProposalSummariesResponse, ProposalFilter, ListOrder, ParticipantApplication, PayoutSimulation, PayoutLeg},
+    contract::migrate,
+    sudo::{sudo_open_ack, save_query_id, sudo_timeout, sudo_tx_query_result, prepare_sudo_payload},
+    utils::validate_shareholders,
 };
 
+use cw_storage_plus::Map;
 use cosmwasm_std::{
-    from_binary,
+    from_binary, to_binary,
     testing::{MockApi, MockQuerier, MockStorage, mock_env},
-    OwnedDeps, Addr, MessageInfo, coins,
+    OwnedDeps, Addr, MessageInfo, coins, coin, Uint128, SystemResult, ContractResult, CosmosMsg,
+    Reply, SubMsgResult, SubMsgResponse, DepsMut, QuerierWrapper, BankMsg, StdResult,
+    Storage, Order, Record, Event, StdError, Decimal, Binary,
 };
 
-use neutron_sdk::bindings::query::NeutronQuery;
+use bech32::{ToBase32, Variant};
+use cosmos_sdk_proto::{
+    cosmos::{bank::v1beta1::MsgSend, base::v1beta1::Coin as ProtoCoin, tx::v1beta1::{TxBody, TxRaw}},
+    Any as ProtoAny,
+};
+use prost::Message;
+
+use neutron_sdk::{
+    bindings::{query::{NeutronQuery, QueryRegisteredQueryResponse}, msg::{IbcFee, NeutronMsg, MsgRegisterInterchainQueryResponse, MsgSubmitTxResponse}, types::{Height, RegisteredQuery}},
+    interchain_queries::{types::QueryType, v045::types::COSMOS_SDK_TRANSFER_MSG_URL},
+    interchain_txs::helpers::get_port_id,
+    query::min_ibc_fee::MinIbcFeeResponse,
+    sudo::msg::RequestPacket,
+    NeutronError,
+};
 
 pub fn mock_dependencies() -> OwnedDeps<MockStorage, MockApi, MockQuerier, NeutronQuery> {
     OwnedDeps {
@@ -26,104 +52,242 @@ pub fn mock_dependencies() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Neutr
     }
 }
 
+pub fn mock_dependencies_with_fee() -> OwnedDeps<MockStorage, MockApi, MockQuerier<NeutronQuery>, NeutronQuery> {
+    let querier = MockQuerier::<NeutronQuery>::new(&[]).with_custom_handler(|query| match query {
+        NeutronQuery::MinIbcFee {} => SystemResult::Ok(ContractResult::Ok(
+            to_binary(&MinIbcFeeResponse {
+                min_fee: IbcFee {
+                    recv_fee: coins(10, "uatom"),
+                    ack_fee: coins(1000, "untrn"),
+                    timeout_fee: coins(1000, "untrn"),
+                },
+            })
+            .unwrap(),
+        )),
+        // every query is a transfers query of the same interchain account
+        NeutronQuery::RegisteredInterchainQuery { query_id } => SystemResult::Ok(ContractResult::Ok(
+            to_binary(&QueryRegisteredQueryResponse {
+                registered_query: RegisteredQuery {
+                    id: *query_id,
+                    owner: "cosmos2contract".to_string(),
+                    keys: vec![],
+                    query_type: QueryType::TX,
+                    transactions_filter: format!(r#"[{{"field":"transfer.recipient","op":"Eq","value":"{}"}}]"#, mock_ica_address()),
+                    connection_id: "connection-0".to_string(),
+                    update_period: 6,
+                    last_submitted_result_local_height: 0,
+                    last_submitted_result_remote_height: Height::default(),
+                    deposit: vec![],
+                    submit_timeout: 0,
+                    registered_at_height: 0,
+                },
+            })
+            .unwrap(),
+        )),
+        _ => SystemResult::Ok(ContractResult::Err("unsupported query".to_string())),
+    });
+
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier,
+        custom_query_type: PhantomData,
+    }
+}
+
+pub fn mock_config(admin: &Addr, query_deposit: u128) -> Config {
+    Config {
+        admin: admin.clone(),
+        registration_fee: Uint128::zero(),
+        query_deposit: Uint128::new(query_deposit),
+        conflict_rules: ConflictRules::default(),
+        bond_rules: BondRules::default(),
+    }
+}
+
+pub fn deliver(store: &mut dyn Storage, applicant: &Addr, proposal_id: u64) {
+    submit_deliverable(store, mock_env(), applicant.clone(), proposal_id, None, "ipfs://work".to_string(), "ab".repeat(32)).unwrap();
+}
+
+pub fn fee_info(sender: &Addr, amount: u128) -> MessageInfo {
+    MessageInfo {
+        sender: sender.clone(),
+        funds: coins(amount, "untrn"),
+    }
+}
+
 #[test]
 fn test_query_errors_queue() {
     let mut deps = mock_dependencies();
 
-    let result = query_errors_queue(deps.as_ref()).unwrap();
-    let result: Vec<(Vec<u8>, String)> = from_binary(&result).unwrap();
+    let result = query_errors_queue(&deps.storage, None, None, None).unwrap();
+    let result: Vec<(u32, ErrorRecord)> = from_binary(&result).unwrap();
 
     assert_eq!(0, result.len());
 
-    let error_msg = "Error message".to_string();
+    let error = ErrorRecord::new(ErrorKind::UnknownQuery, 12, "Error message".to_string());
 
     ERRORS_QUEUE
-        .save(&mut deps.storage, 0u32, &error_msg)
+        .save(&mut deps.storage, 0u32, &error)
         .unwrap();
 
-    let result = query_errors_queue(deps.as_ref()).unwrap();
-    let result: Vec<(Vec<u8>, String)> = from_binary(&result).unwrap();
+    let result = query_errors_queue(&deps.storage, None, None, None).unwrap();
+    let result: Vec<(u32, ErrorRecord)> = from_binary(&result).unwrap();
 
-    assert_eq!(1, result.len());
+    assert_eq!(vec![(0, error)], result);
 }
 
 #[test]
 fn test_errors_queue() {
     let mut store = MockStorage::new();
+    let admin = Addr::unchecked("admin");
+    CONFIG.save(&mut store, &mock_config(&admin, 0)).unwrap();
 
-    let errors = read_errors_from_queue(&store);
-    let errors = errors.unwrap();
-
+    let errors = read_errors_from_queue(&store, None, 10, None).unwrap();
     assert_eq!(0, errors.len());
 
-    let error = "some error message".to_string();
+    let missing = ErrorRecord {
+        channel_id: Some("channel-0".to_string()),
+        sequence: Some(3),
+        ..ErrorRecord::new(ErrorKind::MissingPayload, 10, "Unable to read sudo payload".to_string())
+    };
+    let unknown = ErrorRecord::new(ErrorKind::UnknownQuery, 11, "Unknown KV query id 4".to_string());
 
-    add_error_to_queue(&mut store, error.clone()).unwrap();
+    for error in [&missing, &unknown, &missing, &unknown, &missing] {
+        add_error_to_queue(&mut store, error.clone()).unwrap();
+    }
 
-    let errors = read_errors_from_queue(&store);
-    let errors = errors.unwrap();
+    let errors = read_errors_from_queue(&store, None, 2, None).unwrap();
+    assert_eq!(vec![(0, missing.clone()), (1, unknown.clone())], errors);
 
-    assert_eq!(1, errors.len());
-    assert_eq!(errors, vec![(0u32.to_be_bytes().to_vec(), error.clone())]);
+    let errors = read_errors_from_queue(&store, Some(1), 10, None).unwrap();
+    assert_eq!(vec![2, 3, 4], errors.into_iter().map(|(id, _)| id).collect::<Vec<_>>());
 
-    add_error_to_queue(&mut store, error.clone()).unwrap();
-    add_error_to_queue(&mut store, error.clone()).unwrap();
+    let errors = read_errors_from_queue(&store, None, 10, Some(ErrorKind::UnknownQuery)).unwrap();
+    assert_eq!(vec![(1, unknown.clone()), (3, unknown.clone())], errors);
 
-    let errors = read_errors_from_queue(&store);
-    let errors = errors.unwrap();
+    let err = clear_errors(&mut store, Addr::unchecked("bob"), 2).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized{}, err);
 
-    assert_eq!(3, errors.len());
-    assert_eq!(
-        errors,
-        vec![
-            (0u32.to_be_bytes().to_vec(), error.clone()),
-            (1u32.to_be_bytes().to_vec(), error.clone()),
-            (2u32.to_be_bytes().to_vec(), error)
-        ]
+    let res = clear_errors(&mut store, admin, 2).unwrap();
+    assert!(res.attributes.is_empty());
+    assert_eq!("3", event_attr(&res.events[0], "cleared"));
+    let errors = read_errors_from_queue(&store, None, 10, None).unwrap();
+    assert_eq!(vec![3, 4], errors.into_iter().map(|(id, _)| id).collect::<Vec<_>>());
+
+    // ids aren't reused after clearing
+    assert_eq!(5, add_error_to_queue(&mut store, unknown.clone()).unwrap());
+
+    // errors stored before the kind index existed are found after reindexing
+    Map::<u32, ErrorRecord>::new("errors_queue").save(&mut store, 6, &unknown).unwrap();
+    let errors = read_errors_from_queue(&store, Some(3), 10, Some(ErrorKind::UnknownQuery)).unwrap();
+    assert_eq!(vec![5], errors.into_iter().map(|(id, _)| id).collect::<Vec<_>>());
+
+    assert_eq!(4, reindex_errors(&mut store).unwrap());
+    let errors = read_errors_from_queue(&store, Some(3), 10, Some(ErrorKind::UnknownQuery)).unwrap();
+    assert_eq!(vec![5, 6], errors.into_iter().map(|(id, _)| id).collect::<Vec<_>>());
+}
+
+
+#[test]
+fn test_migrate_legacy_state() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let admin = Addr::unchecked("admin");
+    CONFIG.save(&mut deps.storage, &mock_config(&admin, 0)).unwrap();
+
+    // proposals as they were stored before creators and statuses were recorded
+    deps.storage.set(&PROPOSALS.key(0), br#"{"title":"Test","description":"Description","funding":[]}"#);
+    // applications with shares in whole percents
+    deps.storage.set(
+        &APPLICATIONS.key((0, Addr::unchecked("alice"))),
+        br#"{"applicants":[{"recipient":"alice","percent_share":90}],"auditors":[{"recipient":"carol","percent_share":10}],"deliver_by":{"at_height":20000},"accepted":false,"verifications":[]}"#,
     );
+    // errors as they were stored before they had a kind
+    deps.storage.set(&ERRORS_QUEUE.key(0), br#""first error""#);
+    deps.storage.set(&ERRORS_QUEUE.key(1), br#""second error""#);
+
+    let migrate_deps = DepsMut { storage: &mut deps.storage, api: &deps.api, querier: QuerierWrapper::new(&deps.querier) };
+    migrate(migrate_deps, env.clone(), MigrateMsg {}).unwrap();
+
+    let proposal = PROPOSALS.load(&deps.storage, 0).unwrap();
+    assert_eq!((None, ProposalStatus::Open), (proposal.creator.clone(), proposal.status.clone()));
+    assert_eq!("Test", proposal.title);
+
+    // nobody can act as the creator of a legacy proposal
+    let ends = cw_utils::Expiration::AtHeight(env.block.height + 100);
+    let err = open_selection(&mut deps.storage, env.clone(), Addr::unchecked(""), 0, ends, 1).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized {}, err);
+    let err = set_min_reputation(&mut deps.storage, admin, 0, None).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized {}, err);
+
+    let listed: AllProposalResponse = from_binary(&query_all_proposals(deps.as_ref(), None, None, None, None).unwrap()).unwrap();
+    assert_eq!(None, listed.proposals[0].creator);
+
+    let application = APPLICATIONS.load(&deps.storage, (0, Addr::unchecked("alice"))).unwrap();
+    assert_eq!(vec![GoodFee { recipient: Addr::unchecked("alice"), share_bps: 9000 }], application.applicants);
+    assert_eq!(vec![GoodFee { recipient: Addr::unchecked("carol"), share_bps: 1000 }], application.auditors);
+    assert_eq!((false, vec![]), (application.expired, application.budget));
+    let work: Vec<ParticipantApplication> = from_binary(&query_applications_by_participant(deps.as_ref(), "carol".to_string(), None, None).unwrap()).unwrap();
+    assert_eq!(vec![ParticipantRole::Auditor], work[0].roles);
+
+    let errors = read_errors_from_queue(&deps.storage, None, 10, Some(ErrorKind::Legacy)).unwrap();
+    assert_eq!(vec![
+        (0, ErrorRecord::new(ErrorKind::Legacy, 0, "first error".to_string())),
+        (1, ErrorRecord::new(ErrorKind::Legacy, 0, "second error".to_string())),
+    ], errors);
+    // new errors don't overwrite the migrated ones
+    let id = add_error_to_queue(&mut deps.storage, ErrorRecord::new(ErrorKind::UnknownQuery, env.block.height, "new error".to_string())).unwrap();
+    assert_eq!(2, id);
+    assert_eq!(3, read_errors_from_queue(&deps.storage, None, 10, None).unwrap().len());
 }
 
 
 
 #[test]
 fn test_escrow_funds() {
-    let deps = mock_dependencies();
-    let mut storage = deps.storage;
+    let mut deps = mock_dependencies();
     let env = mock_env();
     let alice = Addr::unchecked("alice");
     let bob = Addr::unchecked("bob");
 
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+
     submit_proposal(
-        &mut storage, 
+        &mut deps.storage, 
+        alice.clone(),
         "Test".to_string(), 
-        "Description".to_string()
+        "Description".to_string(),
+        vec![]
     ).unwrap();
 
     submit_application(
-        &mut storage, 
+        deps.as_mut(), 
         env.clone(), 
         alice.clone(), 
         0, 
         ApplicationSubmission {
-            applicants: vec![ GoodFee { recipient: alice.clone(), percent_share: 99 } ],
-            auditors: vec![ GoodFee { recipient: bob.clone(), percent_share: 1 } ],
+            applicants: vec![ GoodFee { recipient: alice.clone(), share_bps: 9900 } ],
+            auditors: vec![ GoodFee { recipient: bob.clone(), share_bps: 100 } ],
             deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+            budget: vec![],
         })
     .unwrap();
 
     fund_proposal_native(
-        &mut storage, 
+        &mut deps.storage, 
         MessageInfo {
             sender: bob.clone(),
             funds: coins(10_000_000, "untrn"),
         }, 
         0, 
+        None,
         None
     ).unwrap();
 
 
     approve_application(
-        &mut storage,
+        &mut deps.storage,
         &bob,
         0,
         alice.clone()
@@ -132,7 +296,7 @@ fn test_escrow_funds() {
 
 
     accept_application(
-        &mut storage,
+        &mut deps.storage,
         alice.clone(),
         0,
         alice.clone()
@@ -143,3 +307,1890 @@ fn test_escrow_funds() {
     assert_eq!(1, 1)
 
 }
+
+
+
+/// Remote address of the interchain account answered by the mocked transfers query
+pub fn mock_ica_address() -> String {
+    bech32::encode("cosmos", [1u8; 32].to_base32(), Variant::Bech32).unwrap()
+}
+
+/// Raw transaction with a single transfer to the interchain account of the registered query
+fn transfer_tx(sender: &str, denom: &str, amount: u128) -> Binary {
+    let send = MsgSend {
+        from_address: sender.to_string(),
+        to_address: mock_ica_address(),
+        amount: vec![ProtoCoin { denom: denom.to_string(), amount: amount.to_string() }],
+    };
+    let body = TxBody {
+        messages: vec![ProtoAny { type_url: COSMOS_SDK_TRANSFER_MSG_URL.to_string(), value: send.encode_to_vec() }],
+        ..TxBody::default()
+    };
+    Binary(TxRaw { body_bytes: body.encode_to_vec(), ..TxRaw::default() }.encode_to_vec())
+}
+
+#[test]
+fn test_refund_quarantined() {
+    let mut deps = mock_dependencies_with_fee();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let sender = bech32::encode("cosmos", [7u8; 20].to_base32(), Variant::Bech32).unwrap();
+    let port_id = get_port_id(env.contract.address.as_str(), "0-connection-0");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 1000)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    register_ica(&mut deps.storage, env.clone(), fee_info(&alice, 2000), "connection-0".to_string(), 0).unwrap();
+    let version = format!(r#"{{"version":"ics27-1","controller_connection_id":"connection-0","host_connection_id":"connection-7","address":"{}","encoding":"proto3","tx_type":"sdk_multi_msg"}}"#, mock_ica_address());
+    sudo_open_ack(&mut deps.storage, env.clone(), port_id.clone(), "channel-0".to_string(), "channel-1".to_string(), version).unwrap();
+
+    // the registration paid for the balance query of the first denom
+    let res = sudo_tx_query_result(deps.as_mut(), env.clone(), 1, Height::default(), transfer_tx(&sender, "uatom", 300)).unwrap();
+    assert_eq!(1, res.messages.len());
+    // deposits are credited to the same account on Neutron
+    let funder = Addr::unchecked(bech32::encode("neutron", [7u8; 20].to_base32(), Variant::Bech32).unwrap());
+    assert_eq!((funder.as_str(), sender.as_str()), (event_attr(&res.events[0], "funder"), event_attr(&res.events[0], "sender")));
+    assert_eq!(Uint128::new(300), CUSTODY_FUNDS.load(&deps.storage, custody_key(&funder, 0, "uatom", Some(&port_id))).unwrap().amount);
+
+    // nothing pays for a balance query of another denom
+    let res = sudo_tx_query_result(deps.as_mut(), env.clone(), 1, Height::default(), transfer_tx(&sender, "uosmo", 500)).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!("UntrackedDenom", event_attr(&res.events[0], "reason"));
+
+    let deposit = QuarantinedDeposit {
+        sender: sender.clone(),
+        recipient: mock_ica_address(),
+        denom: "uosmo".to_string(),
+        amount: Uint128::new(500),
+        tx_hash: event_attr(&res.events[0], "tx_hash").to_string(),
+        port_id: port_id.clone(),
+        reason: QuarantineReason::UntrackedDenom,
+        refund_pending: false,
+    };
+    let listed: Vec<(u64, QuarantinedDeposit)> = from_binary(
+        &query_quarantined_deposits(&deps.storage, None, None).unwrap()
+    ).unwrap();
+    assert_eq!(vec![(0, deposit)], listed);
+    let id = 0;
+
+    // funds of a sender that isn't an address can't be attributed or refunded
+    let res = sudo_tx_query_result(deps.as_mut(), env.clone(), 1, Height::default(), transfer_tx("cosmos1sender", "uatom", 100)).unwrap();
+    assert_eq!("UnknownSender", event_attr(&res.events[0], "reason"));
+    let err = refund_quarantined(deps.as_mut(), env.clone(), fee_info(&alice, 2000), 1).unwrap_err();
+    assert_eq!(NeutronError::Std(StdError::generic_err("Sender of the deposit can't receive a refund")), err);
+
+    PROPOSALS.update(&mut deps.storage, 0, |p| -> StdResult<_> {
+        let mut p = p.unwrap();
+        p.status = ProposalStatus::Completed;
+        Ok(p)
+    }).unwrap();
+    let res = sudo_tx_query_result(deps.as_mut(), env.clone(), 1, Height::default(), transfer_tx(&sender, "uatom", 200)).unwrap();
+    assert_eq!("ProposalClosed", event_attr(&res.events[0], "reason"));
+    assert_eq!(Uint128::new(300), CUSTODY_FUNDS.load(&deps.storage, custody_key(&funder, 0, "uatom", Some(&port_id))).unwrap().amount);
+
+    // neither the caller nor the fee reserve can cover the fee
+    let err = refund_quarantined(deps.as_mut(), mock_env(), fee_info(&alice, 500), id).unwrap_err();
+    assert_eq!(NeutronError::InsufficientFunds { required: "1500untrn".to_string() }, err);
+
+    fund_fee_reserve(&mut deps.storage, fee_info(&alice, 1800)).unwrap();
+
+    let res = refund_quarantined(deps.as_mut(), mock_env(), fee_info(&alice, 500), id).unwrap();
+    assert_eq!(1, res.messages.len());
+    assert_eq!(Uint128::new(300), FEE_RESERVE.load(&deps.storage).unwrap());
+    match &res.messages[0].msg {
+        CosmosMsg::Custom(NeutronMsg::SubmitTx { connection_id, interchain_account_id, fee, .. }) => {
+            assert_eq!("connection-0", connection_id);
+            assert_eq!("0-connection-0", interchain_account_id);
+            assert_eq!(coins(1000, "untrn"), fee.ack_fee);
+            assert!(fee.recv_fee.is_empty());
+        },
+        msg => panic!("unexpected message {:?}", msg),
+    }
+
+    assert!(QUARANTINED_DEPOSITS.load(&deps.storage, id).unwrap().refund_pending);
+
+    // a second refund can't be started while the first one is in flight
+    let err = refund_quarantined(deps.as_mut(), mock_env(), fee_info(&alice, 2000), id).unwrap_err();
+    assert_eq!(NeutronError::RefundInProgress{}, err);
+
+    let data = to_binary(&MsgSubmitTxResponse { sequence_id: 4, channel: "channel-0".to_string() }).unwrap();
+    let reply_deps = DepsMut { storage: &mut deps.storage, api: &deps.api, querier: QuerierWrapper::new(&deps.querier) };
+    prepare_sudo_payload(reply_deps, mock_env(), Reply {
+        id: SUDO_PAYLOAD_REPLY_ID_START,
+        result: SubMsgResult::Ok(SubMsgResponse { events: vec![], data: Some(data) }),
+    }).unwrap();
+
+    let record = IBC_FEES.load(&deps.storage, ("channel-0".to_string(), 4)).unwrap();
+    assert_eq!(None, record.refunded);
+
+    // the relayer of the timeout keeps the timeout fee, the ack fee is split between the caller and the reserve
+    let res = sudo_timeout(&mut deps.storage, mock_env(), RequestPacket {
+        sequence: Some(4),
+        source_port: Some(port_id.clone()),
+        source_channel: Some("channel-0".to_string()),
+        destination_port: None,
+        destination_channel: None,
+        data: None,
+        timeout_height: None,
+        timeout_timestamp: None,
+    }).unwrap();
+
+    let record = IBC_FEES.load(&deps.storage, ("channel-0".to_string(), 4)).unwrap();
+    assert_eq!(Some(coins(1000, "untrn")), record.refunded);
+    assert_eq!(CosmosMsg::Bank(BankMsg::Send { to_address: "alice".to_string(), amount: coins(250, "untrn") }), res.messages[0].msg);
+    assert_eq!(Uint128::new(1050), FEE_RESERVE.load(&deps.storage).unwrap());
+
+    // the failed refund stays in the outbox instead of being forgotten
+    assert!(QUARANTINED_DEPOSITS.load(&deps.storage, id).unwrap().refund_pending);
+    let stuck: Vec<(u64, PendingOperation)> = from_binary(
+        &query_pending_operations(&deps.storage, None, None, true).unwrap()
+    ).unwrap();
+    assert_eq!(1, stuck.len());
+    let (outbox_id, pending) = stuck[0].clone();
+    assert_eq!(IcaOperation::RefundQuarantined { id }, pending.operation);
+    assert_eq!(1, pending.attempts);
+    assert_eq!(Some("timeout".to_string()), pending.last_error);
+
+    INTERCHAIN_ACCOUNTS.update(&mut deps.storage, port_id.clone(), |ica| -> StdResult<_> {
+        let mut ica = ica.unwrap();
+        ica.status = IcaStatus::Open;
+        Ok(ica)
+    }).unwrap();
+
+    let err = retry_pending(deps.as_mut(), mock_env(), fee_info(&alice, 2500), outbox_id).unwrap_err();
+    assert_eq!(NeutronError::RetryNotDue { retry_after: mock_env().block.height + 100 }, err);
+
+    // surplus of the attached funds goes back to the caller
+    let mut env = mock_env();
+    env.block.height += 100;
+    let res = retry_pending(deps.as_mut(), env.clone(), fee_info(&alice, 2500), outbox_id).unwrap();
+    assert_eq!(CosmosMsg::Bank(BankMsg::Send { to_address: "alice".to_string(), amount: coins(500, "untrn") }), res.messages[1].msg);
+
+    let pending = OUTBOX.load(&deps.storage, outbox_id).unwrap();
+    assert_eq!(2, pending.attempts);
+    assert!(pending.in_flight);
+
+    let err = retry_pending(deps.as_mut(), env.clone(), fee_info(&alice, 2000), outbox_id).unwrap_err();
+    assert_eq!(NeutronError::OperationInFlight{}, err);
+
+    let stuck: Vec<(u64, PendingOperation)> = from_binary(
+        &query_pending_operations(&deps.storage, None, None, true).unwrap()
+    ).unwrap();
+    assert!(stuck.is_empty());
+
+    // an operation failing too many times leaves the outbox and the deposit can be refunded again
+    let data = to_binary(&MsgSubmitTxResponse { sequence_id: 5, channel: "channel-2".to_string() }).unwrap();
+    let reply_deps = DepsMut { storage: &mut deps.storage, api: &deps.api, querier: QuerierWrapper::new(&deps.querier) };
+    prepare_sudo_payload(reply_deps, mock_env(), Reply {
+        id: SUDO_PAYLOAD_REPLY_ID_START + 1,
+        result: SubMsgResult::Ok(SubMsgResponse { events: vec![], data: Some(data) }),
+    }).unwrap();
+    OUTBOX.update(&mut deps.storage, outbox_id, |pending| -> StdResult<_> {
+        let mut pending = pending.unwrap();
+        pending.attempts = MAX_RETRY_ATTEMPTS;
+        Ok(pending)
+    }).unwrap();
+
+    sudo_timeout(&mut deps.storage, mock_env(), RequestPacket {
+        sequence: Some(5),
+        source_port: Some(port_id.clone()),
+        source_channel: Some("channel-2".to_string()),
+        destination_port: None,
+        destination_channel: None,
+        data: None,
+        timeout_height: None,
+        timeout_timestamp: None,
+    }).unwrap();
+
+    assert!(!OUTBOX.has(&deps.storage, outbox_id));
+    assert!(!QUARANTINED_DEPOSITS.load(&deps.storage, id).unwrap().refund_pending);
+    let errors = read_errors_from_queue(&deps.storage, None, 10, Some(ErrorKind::RetriesExhausted)).unwrap();
+    assert_eq!(1, errors.len());
+    assert_eq!(Some(0), errors[0].1.proposal_id);
+
+    // transfers to an account the contract doesn't know are kept aside instead of failing the result
+    ADDRESS_TO_PROPOSAL.remove(&mut deps.storage, mock_ica_address());
+    ADDRESS_TO_PORT.remove(&mut deps.storage, mock_ica_address());
+    let res = sudo_tx_query_result(deps.as_mut(), env.clone(), 1, Height::default(), transfer_tx(&sender, "uatom", 700)).unwrap();
+    assert_eq!("UnknownAccount", event_attr(&res.events[0], "reason"));
+    assert!(res.events[0].attributes.iter().all(|a| a.key != "proposal_id"));
+    let id: u64 = event_attr(&res.events[0], "deposit_id").parse().unwrap();
+    assert_eq!(String::new(), QUARANTINED_DEPOSITS.load(&deps.storage, id).unwrap().port_id);
+    let err = refund_quarantined(deps.as_mut(), env, fee_info(&alice, 2000), id).unwrap_err();
+    assert_eq!(NeutronError::Std(StdError::generic_err("Deposit isn't held by an interchain account of the contract")), err);
+}
+
+
+
+#[test]
+fn test_reconciliation() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let port_id = get_port_id(env.contract.address.as_str(), "0-connection-0");
+
+    PROPOSAL_ICAS.save(&mut deps.storage, (0, "connection-0"), &port_id).unwrap();
+
+    for (sender, denom, amount) in [("alice", "uatom", 100u128), ("bob", "uatom", 50), ("bob", "uosmo", 10)] {
+        CUSTODY_FUNDS.save(&mut deps.storage, custody_key(&Addr::unchecked(sender), 0, denom, Some(&port_id)), &CustodyFunds {
+            amount: Uint128::new(amount),
+            proposal_id: 0,
+            locked: false,
+            remote: Some(port_id.clone()),
+            application: None,
+            excess: Uint128::zero(),
+        }).unwrap();
+    }
+
+    REMOTE_BALANCES.save(&mut deps.storage, (port_id.as_str(), "uatom"), &RemoteBalance {
+        amount: Uint128::new(170),
+        last_submitted_local_height: 12,
+    }).unwrap();
+
+    REMOTE_BALANCES.save(&mut deps.storage, (port_id.as_str(), "uosmo"), &RemoteBalance {
+        amount: Uint128::new(4),
+        last_submitted_local_height: 12,
+    }).unwrap();
+
+    let res: ReconciliationResponse = from_binary(
+        &query_reconciliation(deps.as_ref(), 0).unwrap()
+    ).unwrap();
+
+    assert_eq!(1, res.accounts.len());
+    let account = &res.accounts[0];
+    assert_eq!(port_id, account.port_id);
+    assert_eq!(2, account.denoms.len());
+
+    let atom = account.denoms.iter().find(|d| d.denom == "uatom").unwrap();
+    assert_eq!(Uint128::new(150), atom.custody);
+    assert_eq!(Some(Uint128::new(170)), atom.on_chain);
+    assert_eq!(Uint128::new(20), atom.surplus);
+    assert_eq!(Uint128::zero(), atom.deficit);
+
+    let osmo = account.denoms.iter().find(|d| d.denom == "uosmo").unwrap();
+    assert_eq!(Uint128::zero(), osmo.surplus);
+    assert_eq!(Uint128::new(6), osmo.deficit);
+}
+
+
+
+#[test]
+fn test_query_lifecycle() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let admin = Addr::unchecked("admin");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&admin, 0)).unwrap();
+
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+
+    let port_id = get_port_id(env.contract.address.as_str(), "0-connection-0");
+    let version = r#"{"version":"ics27-1","controller_connection_id":"connection-0","host_connection_id":"connection-1","address":"cosmos1ica","encoding":"proto3","tx_type":"sdk_multi_msg"}"#;
+
+    let res = sudo_open_ack(
+        &mut deps.storage, 
+        env.clone(), 
+        port_id.clone(), 
+        "channel-0".to_string(), 
+        "channel-1".to_string(), 
+        version.to_string()
+    ).unwrap();
+    assert_eq!(1, res.messages.len());
+    assert_eq!(1, PENDING_QUERIES.load(&deps.storage).unwrap().len());
+
+    let reply_deps = DepsMut { storage: &mut deps.storage, api: &deps.api, querier: QuerierWrapper::new(&deps.querier) };
+    save_query_id(reply_deps, env.clone(), Reply {
+        id: 2,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(to_binary(&MsgRegisterInterchainQueryResponse { id: 7 }).unwrap()),
+        }),
+    }).unwrap();
+
+    assert!(PENDING_QUERIES.load(&deps.storage).unwrap().is_empty());
+    let info = PROPOSAL_QUERIES.load(&deps.storage, (0, 7)).unwrap();
+    assert_eq!(QueryKind::Transfers, info.kind);
+    assert_eq!(port_id, info.port_id);
+
+    let err = update_query_period(&mut deps.storage, bob.clone(), 0, 7, 20).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized{}, err);
+
+    update_query_period(&mut deps.storage, alice.clone(), 0, 7, 20).unwrap();
+    update_query_period(&mut deps.storage, admin, 0, 7, 30).unwrap();
+    assert_eq!(30, PROPOSAL_QUERIES.load(&deps.storage, (0, 7)).unwrap().update_period);
+
+    submit_application(
+        deps.as_mut(), 
+        env.clone(), 
+        alice.clone(), 
+        0, 
+        ApplicationSubmission {
+            applicants: vec![ GoodFee { recipient: alice.clone(), share_bps: 9000 } ],
+            auditors: vec![ GoodFee { recipient: bob.clone(), share_bps: 1000 } ],
+            deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+            budget: vec![],
+        }
+    ).unwrap();
+
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: bob.clone(), funds: coins(1000, "untrn") }, 0, None, None).unwrap();
+    approve_application(&mut deps.storage, &bob, 0, alice.clone()).unwrap();
+    accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+
+    deliver(&mut deps.storage, &alice, 0);
+    let res = verify_application(&mut deps.storage, bob, 0, alice).unwrap();
+
+    assert_eq!(
+        vec![CosmosMsg::Custom(NeutronMsg::remove_interchain_query(7))],
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>()
+    );
+    assert_eq!(ProposalStatus::Completed, PROPOSALS.load(&deps.storage, 0).unwrap().status);
+    assert!(!PROPOSAL_QUERIES.has(&deps.storage, (0, 7)));
+}
+
+
+
+#[test]
+fn test_multichain_icas() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let admin = Addr::unchecked("admin");
+    let alice = Addr::unchecked("alice");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&admin, 1000)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+
+    for connection_id in ["connection-0", "connection-1"] {
+        let res = register_ica(&mut deps.storage, env.clone(), fee_info(&alice, 2000), connection_id.to_string(), 0).unwrap();
+        assert_eq!(
+            CosmosMsg::Custom(NeutronMsg::register_interchain_account(
+                connection_id.to_string(),
+                format!("0-{}", connection_id)
+            )),
+            res.messages[0].msg
+        );
+    }
+
+    let err = set_connection_chain(&mut deps.storage, alice, "connection-0".to_string(), "cosmoshub-4".to_string()).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized{}, err);
+    set_connection_chain(&mut deps.storage, admin, "connection-0".to_string(), "cosmoshub-4".to_string()).unwrap();
+
+    let port_id = get_port_id(env.contract.address.as_str(), "0-connection-0");
+    let version = r#"{"version":"ics27-1","controller_connection_id":"connection-0","host_connection_id":"connection-7","address":"cosmos1ica","encoding":"proto3","tx_type":"sdk_multi_msg"}"#;
+    sudo_open_ack(
+        &mut deps.storage, 
+        env.clone(), 
+        port_id.clone(), 
+        "channel-0".to_string(), 
+        "channel-1".to_string(), 
+        version.to_string()
+    ).unwrap();
+
+    assert_eq!(0, ADDRESS_TO_PROPOSAL.load(&deps.storage, "cosmos1ica".to_string()).unwrap());
+    assert_eq!(port_id, ADDRESS_TO_PORT.load(&deps.storage, "cosmos1ica".to_string()).unwrap());
+
+    let addresses = get_deposit_addresses(&deps.storage, 0).unwrap();
+    assert_eq!(2, addresses.len());
+    assert_eq!("connection-0", addresses[0].connection_id);
+    assert_eq!(Some("cosmoshub-4".to_string()), addresses[0].chain_id);
+    assert_eq!(Some("cosmos1ica".to_string()), addresses[0].address);
+    assert_eq!("connection-1", addresses[1].connection_id);
+    assert_eq!(None, addresses[1].chain_id);
+    assert_eq!(None, addresses[1].address);
+    assert_eq!(IcaStatus::Open, addresses[0].status);
+    assert_eq!(IcaStatus::Registering, addresses[1].status);
+}
+
+
+
+#[test]
+fn test_ica_channel_recovery() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 1000)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    register_ica(&mut deps.storage, env.clone(), fee_info(&alice, 2000), "connection-0".to_string(), 0).unwrap();
+
+    let port_id = get_port_id(env.contract.address.as_str(), "0-connection-0");
+    let version = r#"{"version":"ics27-1","controller_connection_id":"connection-0","host_connection_id":"connection-7","address":"cosmos1ica","encoding":"proto3","tx_type":"sdk_multi_msg"}"#;
+
+    let res = sudo_open_ack(&mut deps.storage, env.clone(), port_id.clone(), "channel-0".to_string(), "channel-1".to_string(), version.to_string()).unwrap();
+    assert_eq!(1, res.messages.len());
+
+    let err = register_ica(&mut deps.storage, env.clone(), fee_info(&alice, 1000), "connection-0".to_string(), 0).unwrap_err();
+    assert_eq!(NeutronError::IcaAlreadyRegistered{}, err);
+
+    sudo_timeout(&mut deps.storage, mock_env(), RequestPacket {
+        sequence: Some(1),
+        source_port: Some(port_id.clone()),
+        source_channel: Some("channel-0".to_string()),
+        destination_port: None,
+        destination_channel: None,
+        data: None,
+        timeout_height: None,
+        timeout_timestamp: None,
+    }).unwrap();
+
+    let ica = INTERCHAIN_ACCOUNTS.load(&deps.storage, port_id.clone()).unwrap();
+    assert_eq!(IcaStatus::Closed, ica.status);
+    assert_eq!(Some("cosmos1ica".to_string()), ica.address);
+
+    // anyone can trigger the registration on the same port again, the queries are already paid for
+    let res = register_ica(&mut deps.storage, env.clone(), MessageInfo { sender: alice.clone(), funds: vec![] }, "connection-0".to_string(), 0).unwrap();
+    assert_eq!(1, res.messages.len());
+    assert_eq!(1, QUERY_CREDITS.load(&deps.storage, port_id.as_str()).unwrap().len());
+    let ica = INTERCHAIN_ACCOUNTS.load(&deps.storage, port_id.clone()).unwrap();
+    assert_eq!(IcaStatus::Registering, ica.status);
+    assert_eq!(Some("cosmos1ica".to_string()), ica.address);
+
+    // reopening doesn't register the interchain queries again
+    let res = sudo_open_ack(&mut deps.storage, env, port_id.clone(), "channel-2".to_string(), "channel-3".to_string(), version.to_string()).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(IcaStatus::Open, INTERCHAIN_ACCOUNTS.load(&deps.storage, port_id.clone()).unwrap().status);
+    assert_eq!(port_id, ADDRESS_TO_PORT.load(&deps.storage, "cosmos1ica".to_string()).unwrap());
+}
+
+
+#[test]
+fn test_registration_fees() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    CONFIG.save(&mut deps.storage, &Config {
+        admin: alice.clone(),
+        registration_fee: Uint128::new(500),
+        query_deposit: Uint128::new(1000),
+        conflict_rules: ConflictRules::default(),
+        bond_rules: BondRules::default(),
+    }).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+
+    let err = register_ica(&mut deps.storage, env.clone(), fee_info(&bob, 2000), "connection-0".to_string(), 0).unwrap_err();
+    assert_eq!(NeutronError::InsufficientFunds { required: "2500untrn".to_string() }, err);
+
+    // the excess is sent back to the payer
+    let res = register_ica(&mut deps.storage, env.clone(), fee_info(&bob, 3000), "connection-0".to_string(), 0).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    // one deposit for the transfers query and one for the balance query of the first denom
+    let port_id = get_port_id(env.contract.address.as_str(), "0-connection-0");
+    assert_eq!(2, QUERY_CREDITS.load(&deps.storage, port_id.as_str()).unwrap().len());
+
+    let err = sponsor_queries(&mut deps.storage, fee_info(&alice, 1500), 0, "connection-0".to_string()).unwrap_err();
+    assert_eq!(NeutronError::InsufficientFunds { required: "1000untrn".to_string() }, err);
+
+    sponsor_queries(&mut deps.storage, fee_info(&alice, 2000), 0, "connection-0".to_string()).unwrap();
+    let credits = QUERY_CREDITS.load(&deps.storage, port_id.as_str()).unwrap();
+    assert_eq!(vec![bob.clone(), bob.clone(), alice.clone(), alice.clone()], credits.into_iter().map(|c| c.payer).collect::<Vec<_>>());
+
+    // only the admin collects the registration fees
+    assert_eq!(Uint128::new(500), REGISTRATION_FEES.load(&deps.storage).unwrap());
+    let err = withdraw_registration_fees(deps.as_mut(), bob.clone(), None).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized{}, err);
+
+    let res = withdraw_registration_fees(deps.as_mut(), alice.clone(), Some(bob.to_string())).unwrap();
+    assert_eq!(CosmosMsg::Bank(BankMsg::Send { to_address: bob.to_string(), amount: coins(500, "untrn") }), res.messages[0].msg);
+
+    let err = withdraw_registration_fees(deps.as_mut(), alice, None).unwrap_err();
+    assert_eq!(NeutronError::NoFunds{}, err);
+}
+
+
+#[test]
+fn test_reply_payloads() {
+    let mut deps = mock_dependencies();
+    let fee = IbcFee {
+        recv_fee: vec![],
+        ack_fee: coins(1000, "untrn"),
+        timeout_fee: coins(1000, "untrn"),
+    };
+
+    // several interchain transactions dispatched by the same execution
+    let ids: Vec<u64> = (0..3).map(|id| save_reply_payload(&mut deps.storage, SudoPayload {
+        message: "refund_quarantined".to_string(),
+        port_id: "icacontroller-contract.0-connection-0".to_string(),
+        operation: IcaOperation::RefundQuarantined { id },
+        fee: fee.clone(),
+        outbox_id: id,
+        fee_payment: None,
+    }).unwrap()).collect();
+    assert_eq!(vec![SUDO_PAYLOAD_REPLY_ID_START, SUDO_PAYLOAD_REPLY_ID_START + 1, SUDO_PAYLOAD_REPLY_ID_START + 2], ids);
+
+    for (sequence_id, reply_id) in ids.iter().enumerate() {
+        let data = to_binary(&MsgSubmitTxResponse { sequence_id: sequence_id as u64, channel: "channel-0".to_string() }).unwrap();
+        let reply_deps = DepsMut { storage: &mut deps.storage, api: &deps.api, querier: QuerierWrapper::new(&deps.querier) };
+        prepare_sudo_payload(reply_deps, mock_env(), Reply {
+            id: *reply_id,
+            result: SubMsgResult::Ok(SubMsgResponse { events: vec![], data: Some(data) }),
+        }).unwrap();
+    }
+
+    for sequence_id in 0..3u64 {
+        let payload = read_sudo_payload(&mut deps.storage, "channel-0".to_string(), sequence_id).unwrap();
+        assert_eq!(IcaOperation::RefundQuarantined { id: sequence_id }, payload.operation);
+    }
+
+    // a payload is consumed by its reply
+    let reply_deps = DepsMut { storage: &mut deps.storage, api: &deps.api, querier: QuerierWrapper::new(&deps.querier) };
+    let data = to_binary(&MsgSubmitTxResponse { sequence_id: 9, channel: "channel-0".to_string() }).unwrap();
+    prepare_sudo_payload(reply_deps, mock_env(), Reply {
+        id: ids[0],
+        result: SubMsgResult::Ok(SubMsgResponse { events: vec![], data: Some(data) }),
+    }).unwrap_err();
+}
+
+
+#[test]
+fn test_proposal_listing() {
+    let mut deps = mock_dependencies();
+    let alice = Addr::unchecked("alice");
+    let carol = Addr::unchecked("carol");
+
+    for (creator, tags) in [(&alice, vec!["DeFi ", "tooling"]), (&carol, vec!["tooling"]), (&alice, vec![]), (&carol, vec!["defi"])] {
+        let tags = tags.into_iter().map(String::from).collect();
+        submit_proposal(&mut deps.storage, creator.clone(), "Test".to_string(), "Description".to_string(), tags).unwrap();
+    }
+
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: carol.clone(), funds: coins(100, "uatom") }, 2, None, None).unwrap();
+    PROPOSALS.update(&mut deps.storage, 3, |p| -> StdResult<_> {
+        let mut p = p.unwrap();
+        p.status = ProposalStatus::Completed;
+        Ok(p)
+    }).unwrap();
+
+    let list = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, NeutronQuery>, start_after, limit, order, filter| -> Vec<u64> {
+        let res: ProposalSummariesResponse = from_binary(
+            &query_proposal_summaries(deps.as_ref(), start_after, limit, order, filter).unwrap()
+        ).unwrap();
+        res.proposals.into_iter().map(|p| p.id).collect()
+    };
+
+    assert_eq!(vec![0, 1], list(&deps, None, Some(2), None, None));
+    assert_eq!(vec![2, 3], list(&deps, Some(1), None, None, None));
+    assert_eq!(vec![2, 1, 0], list(&deps, Some(3), None, Some(ListOrder::Descending), None));
+
+    let filter = |f: ProposalFilter| Some(f);
+    assert_eq!(vec![0, 2], list(&deps, None, None, None, filter(ProposalFilter { creator: Some("alice".to_string()), ..Default::default() })));
+    assert_eq!(vec![0, 3], list(&deps, None, None, None, filter(ProposalFilter { tag: Some("defi".to_string()), ..Default::default() })));
+    assert_eq!(vec![3], list(&deps, None, None, None, filter(ProposalFilter { status: Some(ProposalStatus::Completed), ..Default::default() })));
+    assert_eq!(vec![2], list(&deps, None, None, None, filter(ProposalFilter { denom: Some("uatom".to_string()), ..Default::default() })));
+    assert_eq!(vec![2], list(&deps, None, None, None, filter(ProposalFilter { funded_by: Some("carol".to_string()), ..Default::default() })));
+    assert!(list(&deps, None, None, None, filter(ProposalFilter { tag: Some("defi".to_string()), creator: Some("carol".to_string()), status: Some(ProposalStatus::Open), ..Default::default() })).is_empty());
+
+    let res: AllProposalResponse = from_binary(
+        &query_all_proposals(deps.as_ref(), None, None, None, filter(ProposalFilter { tag: Some("tooling".to_string()), ..Default::default() })).unwrap()
+    ).unwrap();
+    assert_eq!(2, res.proposals.len());
+    assert_eq!(vec!["defi".to_string(), "tooling".to_string()], res.proposals[0].tags);
+    assert_eq!(None, res.next_start_after);
+
+    let next = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, NeutronQuery>, start_after, filter| -> (usize, Option<u64>) {
+        let res: ProposalSummariesResponse = from_binary(
+            &query_proposal_summaries(deps.as_ref(), start_after, None, None, filter).unwrap()
+        ).unwrap();
+        (res.proposals.len(), res.next_start_after)
+    };
+    assert_eq!((2, None), next(&deps, None, Some(ProposalFilter { creator: Some("carol".to_string()), ..Default::default() })));
+
+    // a filter matching nothing stops after the scan limit and hands out where to continue
+    for _ in 0..150 {
+        submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    }
+    let carol_filter = || Some(ProposalFilter { creator: Some("carol".to_string()), ..Default::default() });
+    assert_eq!((2, Some(99)), next(&deps, None, carol_filter()));
+    assert_eq!((0, None), next(&deps, Some(99), carol_filter()));
+    assert_eq!((10, Some(9)), next(&deps, None, None));
+}
+
+
+
+#[test]
+fn test_custody_per_proposal() {
+    let mut deps = mock_dependencies();
+    let alice = Addr::unchecked("alice");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+
+    for _ in 0..2 {
+        submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    }
+
+    // funds of the same denom for different proposals are kept apart and repeated funding adds up
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: carol.clone(), funds: coins(100, "uatom") }, 0, None, None).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: carol.clone(), funds: coins(50, "uatom") }, 1, None, None).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: carol.clone(), funds: coins(20, "uatom") }, 0, None, None).unwrap();
+
+    assert_eq!(Uint128::new(120), CUSTODY_FUNDS.load(&deps.storage, custody_key(&carol, 0, "uatom", None)).unwrap().amount);
+    assert_eq!(Uint128::new(50), CUSTODY_FUNDS.load(&deps.storage, custody_key(&carol, 1, "uatom", None)).unwrap().amount);
+
+    let res: ProposalSummariesResponse = from_binary(
+        &query_proposal_summaries(deps.as_ref(), None, None, None, Some(ProposalFilter { funded_by: Some("carol".to_string()), ..Default::default() })).unwrap()
+    ).unwrap();
+    assert_eq!(vec![0, 1], res.proposals.into_iter().map(|p| p.id).collect::<Vec<_>>());
+
+    // entries stored under (funder, denom) move to the key with the proposal and their old indexes are dropped
+    let legacy = Map::<(&Addr, &str), CustodyFunds>::new("custody_funds");
+    let funds = CustodyFunds {
+        amount: Uint128::new(30),
+        proposal_id: 1,
+        locked: false,
+        remote: None,
+        application: None,
+        excess: Uint128::zero(),
+    };
+    legacy.save(&mut deps.storage, (&dave, "uatom"), &funds).unwrap();
+    Map::<(u64, &Addr), u32>::new("custody_funds__proposal").save(&mut deps.storage, (1, &dave), &1).unwrap();
+    // remote funders move to their address on Neutron
+    let remote = Addr::unchecked(bech32::encode("cosmos", [6u8; 32].to_base32(), Variant::Bech32).unwrap());
+    let local = Addr::unchecked(bech32::encode("neutron", [6u8; 32].to_base32(), Variant::Bech32).unwrap());
+    let remote_funds = CustodyFunds { remote: Some("icacontroller-1".to_string()), ..funds.clone() };
+    legacy.save(&mut deps.storage, (&remote, "uatom"), &remote_funds).unwrap();
+
+    assert_eq!(2, migrate_custody_funds(&mut deps.storage).unwrap());
+    assert_eq!(funds, CUSTODY_FUNDS.load(&deps.storage, custody_key(&dave, 1, "uatom", None)).unwrap());
+    assert_eq!(remote_funds, CUSTODY_FUNDS.load(&deps.storage, custody_key(&local, 1, "uatom", Some("icacontroller-1"))).unwrap());
+    assert!(legacy.is_empty(&deps.storage));
+    assert!(Map::<(u64, &Addr), u32>::new("custody_funds__proposal").is_empty(&deps.storage));
+    assert_eq!(3, CUSTODY_FUNDS.idx.proposal.prefix(1).keys(&deps.storage, None, None, Order::Ascending).count());
+
+    assert_eq!(0, migrate_custody_funds(&mut deps.storage).unwrap());
+}
+
+
+
+#[test]
+fn test_remote_recipients() {
+    let api = MockApi::default();
+    let remote = bech32::encode("cosmos", [2u8; 32].to_base32(), Variant::Bech32).unwrap();
+    let share = |recipient: &str| GoodFee { recipient: Addr::unchecked(recipient), share_bps: 5000 };
+
+    // remote addresses are kept in lowercase
+    let mut group = vec![share(&remote.to_uppercase()), share("alice")];
+    assert_eq!(10_000, validate_shareholders(&api, &mut group).unwrap());
+    assert_eq!(Addr::unchecked(&remote), group[0].recipient);
+
+    let mut group = vec![share(&remote), share(&remote.to_uppercase())];
+    let err = validate_shareholders(&api, &mut group).unwrap_err();
+    assert_eq!(NeutronError::DuplicateShareholder { recipient: remote.clone() }, err);
+
+    // local addresses have to pass the address validation of the chain
+    let local = bech32::encode("neutron", [2u8; 32].to_base32(), Variant::Bech32).unwrap().to_uppercase();
+    let err = validate_shareholders(&api, &mut [share(&local)]).unwrap_err();
+    assert_eq!(NeutronError::InvalidRecipient { recipient: local }, err);
+}
+
+
+#[test]
+fn test_remote_recipient_payout() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+    let frank = Addr::unchecked("frank");
+    let remote = bech32::encode("cosmos", [5u8; 32].to_base32(), Variant::Bech32).unwrap();
+    let local = Addr::unchecked(bech32::encode("neutron", [5u8; 32].to_base32(), Variant::Bech32).unwrap());
+    let port_id = get_port_id(env.contract.address.as_str(), "0-connection-0");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: Addr::unchecked(&remote), share_bps: 6000 }, GoodFee { recipient: alice.clone(), share_bps: 3000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    }).unwrap();
+
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: dave.clone(), funds: coins(100, "uatom") }, 0, None, None).unwrap();
+    CUSTODY_FUNDS.save(&mut deps.storage, custody_key(&frank, 0, "uosmo", Some(&port_id)), &CustodyFunds {
+        amount: Uint128::new(50),
+        proposal_id: 0,
+        locked: false,
+        remote: Some(port_id.clone()),
+        application: None,
+        excess: Uint128::zero(),
+    }).unwrap();
+    PROPOSAL_FUNDING.save(&mut deps.storage, (0, "uosmo"), &ProjectFunding { sender: frank.clone(), amount: Uint128::new(50), auto_agree: false, native: false }).unwrap();
+    for funder in [&dave, &frank] {
+        approve_application(&mut deps.storage, funder, 0, alice.clone()).unwrap();
+    }
+    accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+    deliver(&mut deps.storage, &alice, 0);
+    let res = verify_application(&mut deps.storage, carol.clone(), 0, alice.clone()).unwrap();
+
+    // funds of the interchain account are sent to the remote recipient from the account
+    let (outbox_id, pending) = OUTBOX.range(&deps.storage, None, None, Order::Ascending).next().unwrap().unwrap();
+    assert_eq!(IcaOperation::WithdrawCustody {
+        funder: local.clone(),
+        proposal_id: 0,
+        recipient: remote.clone(),
+        denom: "uosmo".to_string(),
+        amount: Uint128::new(30),
+    }, pending.operation);
+    assert_eq!(port_id, pending.port_id);
+    let payout = res.events.iter().find(|e| e.ty == "crossfund-payout" && event_attr(e, "denom") == "uosmo" && event_attr(e, "recipient") == remote).unwrap();
+    assert_eq!(outbox_id.to_string(), event_attr(payout, "outbox_id"));
+    assert!(!CUSTODY_FUNDS.has(&deps.storage, custody_key(&Addr::unchecked(&remote), 0, "uosmo", Some(&port_id))));
+
+    // native funds wait in custody of the same account on Neutron
+    let funds = CUSTODY_FUNDS.load(&deps.storage, custody_key(&local, 0, "uatom", None)).unwrap();
+    assert_eq!((Uint128::new(60), None), (funds.amount, funds.remote));
+    let res = withdraw_funds(&mut deps.storage, env.clone(), MessageInfo { sender: local.clone(), funds: vec![] }).unwrap();
+    assert_eq!(vec![CosmosMsg::Bank(BankMsg::Send { to_address: local.to_string(), amount: coins(60, "uatom") })],
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>());
+
+    // local recipients keep the remote funds in custody
+    let funds = CUSTODY_FUNDS.load(&deps.storage, custody_key(&alice, 0, "uosmo", Some(&port_id))).unwrap();
+    assert_eq!(Uint128::new(15), funds.amount);
+}
+
+
+#[test]
+fn test_dispute_distribution() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+    let erin = Addr::unchecked("erin");
+    let remote = Addr::unchecked("cosmos1remote");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: bob.clone(), funds: coins(300, "uatom") }, 0, None, None).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: dave.clone(), funds: coins(50, "uosmo") }, 0, None, None).unwrap();
+    CUSTODY_FUNDS.save(&mut deps.storage, custody_key(&remote, 0, "uatom", Some("icacontroller-0")), &CustodyFunds {
+        amount: Uint128::new(100),
+        proposal_id: 0,
+        locked: false,
+        remote: Some("icacontroller-0".to_string()),
+        application: None,
+        excess: Uint128::zero(),
+    }).unwrap();
+
+    for auditor in [&carol, &erin] {
+        register_auditor(&mut deps.storage, fee_info(auditor, 1_000_000), "ipfs://auditor".to_string()).unwrap();
+    }
+
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 500 }, GoodFee { recipient: erin.clone(), share_bps: 500 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    }).unwrap();
+    for funder in [&bob, &dave, &remote] {
+        approve_application(&mut deps.storage, funder, 0, alice.clone()).unwrap();
+    }
+    accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+    deliver(&mut deps.storage, &alice, 0);
+
+    // every auditor has to verify the application first
+    verify_application(&mut deps.storage, carol.clone(), 0, alice.clone()).unwrap();
+    let err = raise_dispute(&mut deps.storage, bob.clone(), 0, alice.clone(), "Nothing delivered".to_string()).unwrap_err();
+    assert_eq!(NeutronError::CantDispute {}, err);
+    verify_application(&mut deps.storage, erin.clone(), 0, alice.clone()).unwrap();
+
+    raise_dispute(&mut deps.storage, bob.clone(), 0, alice.clone(), "Nothing delivered".to_string()).unwrap();
+    let err = raise_dispute(&mut deps.storage, dave.clone(), 0, alice.clone(), "Nothing delivered".to_string()).unwrap_err();
+    assert_eq!(NeutronError::DisputeExists { id: 0 }, err);
+
+    // a rejected dispute lets the funders dispute again
+    resolve_dispute(&mut deps.storage, alice.clone(), 0, false).unwrap();
+    raise_dispute(&mut deps.storage, dave.clone(), 0, alice.clone(), "Nothing delivered".to_string()).unwrap();
+
+    // each denom weighs half, bob paid 3/4 of the uatom and dave all of the uosmo
+    let res = resolve_dispute(&mut deps.storage, alice.clone(), 1, true).unwrap();
+    assert_eq!(vec![
+        CosmosMsg::Bank(BankMsg::Send { to_address: "bob".to_string(), amount: coins(750_000, "untrn") }),
+        CosmosMsg::Bank(BankMsg::Send { to_address: "dave".to_string(), amount: coins(1_000_000, "untrn") }),
+    ], res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>());
+    assert_eq!(Uint128::new(250_000), CUSTODY_FUNDS.load(&deps.storage, custody_key(&remote, 0, "untrn", None)).unwrap().amount);
+    assert_eq!(Uint128::zero(), FEE_RESERVE.may_load(&deps.storage).unwrap().unwrap_or_default());
+
+    let err = raise_dispute(&mut deps.storage, bob.clone(), 0, alice.clone(), "Still nothing".to_string()).unwrap_err();
+    assert_eq!(NeutronError::DisputeExists { id: 1 }, err);
+}
+
+
+#[test]
+fn test_withdraw_remote_funds() {
+    let mut deps = mock_dependencies_with_fee();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let frank = Addr::unchecked(bech32::encode("neutron", [3u8; 32].to_base32(), Variant::Bech32).unwrap());
+    let port_id = get_port_id(env.contract.address.as_str(), "0-connection-0");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 1000)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    register_ica(&mut deps.storage, env.clone(), fee_info(&alice, 2000), "connection-0".to_string(), 0).unwrap();
+    let version = format!(r#"{{"version":"ics27-1","controller_connection_id":"connection-0","host_connection_id":"connection-7","address":"{}","encoding":"proto3","tx_type":"sdk_multi_msg"}}"#, mock_ica_address());
+    sudo_open_ack(&mut deps.storage, env.clone(), port_id.clone(), "channel-0".to_string(), "channel-1".to_string(), version).unwrap();
+
+    // the same account funds natively and from the remote chain
+    let remote = bech32::encode("cosmos", [3u8; 32].to_base32(), Variant::Bech32).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: frank.clone(), funds: coins(100, "uosmo") }, 0, None, None).unwrap();
+    sudo_tx_query_result(deps.as_mut(), env.clone(), 1, Height::default(), transfer_tx(&remote, "uatom", 300)).unwrap();
+    assert!(!CUSTODY_FUNDS.has(&deps.storage, custody_key(&Addr::unchecked(&remote), 0, "uatom", Some(&port_id))));
+
+    let err = withdraw_funds(&mut deps.storage, env.clone(), fee_info(&frank, 100)).unwrap_err();
+    assert!(matches!(err, NeutronError::Std(StdError::GenericErr { .. })));
+
+    // native funds are sent right away and remote ones wait in the outbox for the interchain account
+    let res = withdraw_funds(&mut deps.storage, env.clone(), MessageInfo { sender: frank.clone(), funds: vec![] }).unwrap();
+    assert_eq!(vec![CosmosMsg::Bank(BankMsg::Send { to_address: frank.to_string(), amount: coins(100, "uosmo") })],
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>());
+    let outbox_id: u64 = event_attr(&res.events[0], "outbox_id").parse().unwrap();
+
+    assert!(CUSTODY_FUNDS.may_load(&deps.storage, custody_key(&frank, 0, "uatom", Some(&port_id))).unwrap().is_none());
+    assert!(CUSTODY_FUNDS.may_load(&deps.storage, custody_key(&frank, 0, "uosmo", None)).unwrap().is_none());
+    let err = withdraw_funds(&mut deps.storage, env.clone(), MessageInfo { sender: frank.clone(), funds: vec![] }).unwrap_err();
+    assert_eq!(NeutronError::NoFunds {}, err);
+
+    let operation = IcaOperation::WithdrawCustody {
+        funder: frank.clone(),
+        proposal_id: 0,
+        recipient: remote,
+        denom: "uatom".to_string(),
+        amount: Uint128::new(300),
+    };
+    assert_eq!(operation, OUTBOX.load(&deps.storage, outbox_id).unwrap().operation);
+
+    let res = retry_pending(deps.as_mut(), env.clone(), fee_info(&alice, 2000), outbox_id).unwrap();
+    assert!(matches!(res.messages[0].msg, CosmosMsg::Custom(NeutronMsg::SubmitTx { .. })));
+
+    // a withdrawal that can't be delivered goes back to custody
+    let data = to_binary(&MsgSubmitTxResponse { sequence_id: 1, channel: "channel-0".to_string() }).unwrap();
+    let reply_deps = DepsMut { storage: &mut deps.storage, api: &deps.api, querier: QuerierWrapper::new(&deps.querier) };
+    prepare_sudo_payload(reply_deps, env.clone(), Reply {
+        id: res.messages[0].id,
+        result: SubMsgResult::Ok(SubMsgResponse { events: vec![], data: Some(data) }),
+    }).unwrap();
+    OUTBOX.update(&mut deps.storage, outbox_id, |pending| -> StdResult<_> {
+        let mut pending = pending.unwrap();
+        pending.attempts = MAX_RETRY_ATTEMPTS;
+        Ok(pending)
+    }).unwrap();
+
+    sudo_timeout(&mut deps.storage, env, RequestPacket {
+        sequence: Some(1),
+        source_port: Some(port_id.clone()),
+        source_channel: Some("channel-0".to_string()),
+        destination_port: None,
+        destination_channel: None,
+        data: None,
+        timeout_height: None,
+        timeout_timestamp: None,
+    }).unwrap();
+
+    assert!(!OUTBOX.has(&deps.storage, outbox_id));
+    let funds = CUSTODY_FUNDS.load(&deps.storage, custody_key(&frank, 0, "uatom", Some(&port_id))).unwrap();
+    assert_eq!((Uint128::new(300), Some(port_id)), (funds.amount, funds.remote));
+}
+
+/// Storage that counts every read so tests can check how much of the state a call touches
+struct CountingStorage {
+    inner: MockStorage,
+    reads: Cell<usize>,
+}
+
+impl Storage for CountingStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.reads.set(self.reads.get() + 1);
+        self.inner.get(key)
+    }
+
+    fn range<'a>(&'a self, start: Option<&[u8]>, end: Option<&[u8]>, order: Order) -> Box<dyn Iterator<Item = Record> + 'a> {
+        Box::new(self.inner.range(start, end, order).inspect(move |_| self.reads.set(self.reads.get() + 1)))
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.inner.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.inner.remove(key)
+    }
+}
+
+#[test]
+fn test_locked_funds_bounded_iteration() {
+    let mut store = CountingStorage { inner: MockStorage::new(), reads: Cell::new(0) };
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    // plenty of unrelated custody funds of other proposals
+    for proposal_id in 1..=100u64 {
+        for denom in ["uatom", "untrn"] {
+            let funder = Addr::unchecked(format!("funder{}", proposal_id));
+            CUSTODY_FUNDS.save(&mut store, custody_key(&funder, proposal_id, denom, None), &CustodyFunds {
+                amount: Uint128::new(100),
+                proposal_id,
+                locked: true,
+                remote: None,
+                application: Some(alice.clone()),
+                excess: Uint128::zero(),
+            }).unwrap();
+        }
+    }
+
+    for (funder, application) in [("carol", &alice), ("dave", &alice), ("erin", &bob)] {
+        CUSTODY_FUNDS.save(&mut store, custody_key(&Addr::unchecked(funder), 0, "uatom", None), &CustodyFunds {
+            amount: Uint128::new(10),
+            proposal_id: 0,
+            locked: true,
+            remote: None,
+            application: Some(application.clone()),
+            excess: Uint128::zero(),
+        }).unwrap();
+    }
+
+    store.reads.set(0);
+    let funds = get_application_locked_funds(&store, 0, &alice).unwrap();
+
+    let mut funders: Vec<Addr> = funds.into_iter().map(|((funder, _), _)| funder).collect();
+    funders.sort();
+    assert_eq!(vec![Addr::unchecked("carol"), Addr::unchecked("dave")], funders);
+    // an index entry and a primary record per matching funds
+    assert!(store.reads.get() <= 4, "read {} entries", store.reads.get());
+
+    // removing the funds also removes them from the indexes
+    CUSTODY_FUNDS.remove(&mut store, custody_key(&Addr::unchecked("carol"), 0, "uatom", None)).unwrap();
+    assert_eq!(1, get_application_locked_funds(&store, 0, &alice).unwrap().len());
+}
+
+
+#[test]
+fn test_application_queries() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+
+    let submission = |applicant: &Addr, auditor: &Addr| ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: applicant.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: auditor.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    };
+
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission(&alice, &carol)).unwrap();
+    submit_application(deps.as_mut(), env.clone(), dave.clone(), 0, submission(&dave, &carol)).unwrap();
+
+    let by_participant = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, NeutronQuery>, address: &str, start_after| -> Vec<ParticipantApplication> {
+        from_binary(&query_applications_by_participant(deps.as_ref(), address.to_string(), start_after, None).unwrap()).unwrap()
+    };
+
+    let work = by_participant(&deps, "carol", None);
+    assert_eq!(2, work.len());
+    assert!(work.iter().all(|w| w.roles == vec![ParticipantRole::Auditor]));
+
+    let rest = by_participant(&deps, "carol", Some((work[0].proposal_id, work[0].sender.to_string())));
+    assert_eq!(vec![work[1].clone()], rest);
+
+    let own = by_participant(&deps, "alice", None);
+    assert_eq!(1, own.len());
+    assert_eq!(alice, own[0].sender);
+    assert_eq!(vec![ParticipantRole::Applicant], own[0].roles);
+
+    // resubmitting replaces the participants of the application
+    update_conflict_rules(&mut deps.storage, alice.clone(), ConflictRules { separate_auditors: false, max_auditor_funding_bps: None }).unwrap();
+    submit_application(deps.as_mut(), env.clone(), dave.clone(), 0, submission(&dave, &dave)).unwrap();
+    assert_eq!(1, by_participant(&deps, "carol", None).len());
+    assert_eq!(vec![ParticipantRole::Applicant, ParticipantRole::Auditor], by_participant(&deps, "dave", None)[0].roles);
+
+    let applications: Vec<(Addr, Application)> = from_binary(
+        &query_applications(deps.as_ref(), 0, None, Some(1)).unwrap()
+    ).unwrap();
+    assert_eq!(alice, applications[0].0);
+    let applications: Vec<(Addr, Application)> = from_binary(
+        &query_applications(deps.as_ref(), 0, Some("alice".to_string()), None).unwrap()
+    ).unwrap();
+    assert_eq!(vec![dave.clone()], applications.into_iter().map(|(sender, _)| sender).collect::<Vec<_>>());
+
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: carol.clone(), funds: coins(100, "uatom") }, 0, None, None).unwrap();
+    approve_application(&mut deps.storage, &carol, 0, dave.clone()).unwrap();
+
+    let funding: Vec<(String, Uint128)> = from_binary(
+        &query_application_funding(deps.as_ref(), 0, "dave".to_string()).unwrap()
+    ).unwrap();
+    assert_eq!(vec![("uatom".to_string(), Uint128::new(100))], funding);
+
+    // participants on other chains are looked up by their bech32 address in any case
+    let remote = bech32::encode("cosmos", [4u8; 32].to_base32(), Variant::Bech32).unwrap();
+    let erin = Addr::unchecked("erin");
+    submit_application(deps.as_mut(), env.clone(), erin.clone(), 0, submission(&Addr::unchecked(&remote), &carol)).unwrap();
+    let remote_work = by_participant(&deps, &remote.to_uppercase(), None);
+    assert_eq!(vec![(erin, vec![ParticipantRole::Applicant])], remote_work.into_iter().map(|w| (w.sender, w.roles)).collect::<Vec<_>>());
+}
+
+
+#[test]
+fn test_simulate_payout() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let carol = Addr::unchecked("carol");
+    let port_id = get_port_id(env.contract.address.as_str(), "0-connection-0");
+    let other_port_id = get_port_id(env.contract.address.as_str(), "0-connection-1");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 6700 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 3300 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    }).unwrap();
+
+    // the same denom held by two accounts of the proposal is paid out from each of them
+    let deposits = [
+        ("dave", "uatom", 101u128, None),
+        ("erin", "uatom", 100, None),
+        ("dave", "uosmo", 10, Some(port_id.clone())),
+        ("dave", "uosmo", 20, Some(other_port_id.clone())),
+    ];
+    for (funder, denom, amount, remote) in deposits {
+        CUSTODY_FUNDS.save(&mut deps.storage, custody_key(&Addr::unchecked(funder), 0, denom, remote.as_deref()), &CustodyFunds {
+            amount: Uint128::new(amount),
+            proposal_id: 0,
+            locked: true,
+            remote: remote.clone(),
+            application: Some(alice.clone()),
+            excess: Uint128::zero(),
+        }).unwrap();
+    }
+
+    let res: PayoutSimulation = from_binary(
+        &query_simulate_payout(deps.as_ref(), 0, "alice".to_string()).unwrap()
+    ).unwrap();
+
+    let leg = |recipient: &Addr, denom: &str, amount: u128, remote: Option<String>| PayoutLeg {
+        recipient: recipient.clone(),
+        denom: denom.to_string(),
+        amount: Uint128::new(amount),
+        remote,
+    };
+
+    assert_eq!(vec![
+        leg(&alice, "uatom", 134, None),
+        leg(&carol, "uatom", 66, None),
+        leg(&alice, "uosmo", 6, Some(port_id.clone())),
+        leg(&carol, "uosmo", 3, Some(port_id)),
+        leg(&alice, "uosmo", 13, Some(other_port_id.clone())),
+        leg(&carol, "uosmo", 6, Some(other_port_id)),
+    ], res.legs);
+    assert_eq!(vec![coin(1, "uatom"), coin(2, "uosmo")], res.dust);
+
+    // nothing has been moved
+    assert_eq!(4, get_application_locked_funds(&deps.storage, 0, &alice).unwrap().len());
+}
+
+
+fn event_attr<'a>(event: &'a Event, key: &str) -> &'a str {
+    event.attributes.iter().find(|a| a.key == key).map(|a| a.value.as_str()).unwrap_or_default()
+}
+
+
+#[test]
+fn test_events() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+
+    let res = submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec!["Rust".to_string(), "ibc".to_string()]).unwrap();
+    assert_eq!("crossfund-proposal-created", res.events[0].ty);
+    assert_eq!("0", event_attr(&res.events[0], "proposal_id"));
+    assert_eq!("alice", event_attr(&res.events[0], "creator"));
+    assert_eq!("ibc,rust", event_attr(&res.events[0], "tags"));
+
+    let res = submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    }).unwrap();
+    assert_eq!("crossfund-application-submitted", res.events[0].ty);
+
+    let res = fund_proposal_native(&mut deps.storage, MessageInfo { sender: dave.clone(), funds: vec![coin(100, "uatom"), coin(0, "uosmo")] }, 0, None, None).unwrap();
+    assert_eq!(1, res.events.len());
+    assert_eq!("crossfund-proposal-funded", res.events[0].ty);
+    assert_eq!("native", event_attr(&res.events[0], "source"));
+    assert_eq!("100", event_attr(&res.events[0], "amount"));
+    assert_eq!("uatom", event_attr(&res.events[0], "denom"));
+
+    let res = approve_application(&mut deps.storage, &dave, 0, alice.clone()).unwrap();
+    assert_eq!("crossfund-application-approved", res.events[0].ty);
+    assert_eq!("dave", event_attr(&res.events[0], "funder"));
+
+    let res = accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+    assert_eq!("crossfund-application-accepted", res.events[0].ty);
+    assert_eq!("true", event_attr(&res.events[0], "auto_agreed"));
+
+    // accepting again doesn't agree to the funds a second time
+    let err = accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap_err();
+    assert_eq!(NeutronError::AlreadyAccepted {}, err);
+    let funding: Vec<(String, Uint128)> = from_binary(&query_application_funding(deps.as_ref(), 0, "alice".to_string()).unwrap()).unwrap();
+    assert_eq!(vec![("uatom".to_string(), Uint128::new(100))], funding);
+
+    deliver(&mut deps.storage, &alice, 0);
+    let res = verify_application(&mut deps.storage, carol.clone(), 0, alice.clone()).unwrap();
+    let types: Vec<&str> = res.events.iter().map(|e| e.ty.as_str()).collect();
+    assert_eq!(vec!["crossfund-application-verified", "crossfund-payout", "crossfund-payout", "crossfund-proposal-completed"], types);
+    assert_eq!("true", event_attr(&res.events[0], "complete"));
+    assert_eq!(("alice", "90", "native"), (event_attr(&res.events[1], "recipient"), event_attr(&res.events[1], "amount"), event_attr(&res.events[1], "kind")));
+    assert_eq!(("carol", "10"), (event_attr(&res.events[2], "recipient"), event_attr(&res.events[2], "amount")));
+
+    // interchain accounts report the address they were opened with
+    let port_id = get_port_id(env.contract.address.as_str(), "0-connection-0");
+    let version = r#"{"version":"ics27-1","controller_connection_id":"connection-0","host_connection_id":"connection-1","address":"cosmos1ica","encoding":"proto3","tx_type":"sdk_multi_msg"}"#;
+    let res = sudo_open_ack(&mut deps.storage, env, port_id.clone(), "channel-0".to_string(), "channel-1".to_string(), version.to_string()).unwrap();
+    assert_eq!("crossfund-ica-opened", res.events[0].ty);
+    assert_eq!(port_id, event_attr(&res.events[0], "port_id"));
+    assert_eq!("cosmos1ica", event_attr(&res.events[0], "address"));
+    assert_eq!("false", event_attr(&res.events[0], "reopened"));
+}
+
+
+#[test]
+fn test_application_shares() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let carol = Addr::unchecked("carol");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+
+    let submission = |applicants: Vec<(&str, u16)>, auditors: Vec<(&str, u16)>| ApplicationSubmission {
+        applicants: applicants.into_iter().map(|(r, s)| GoodFee { recipient: Addr::unchecked(r), share_bps: s }).collect(),
+        auditors: auditors.into_iter().map(|(r, s)| GoodFee { recipient: Addr::unchecked(r), share_bps: s }).collect(),
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    };
+
+    let err = submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission(vec![("alice", 9999)], vec![("carol", 0)])).unwrap_err();
+    assert_eq!(NeutronError::ZeroShare { recipient: "carol".to_string() }, err);
+
+    let err = submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission(vec![("alice", 4500), ("alice", 4500)], vec![("carol", 1000)])).unwrap_err();
+    assert_eq!(NeutronError::DuplicateShareholder { recipient: "alice".to_string() }, err);
+
+    // large shares don't wrap around to a valid total
+    let err = submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission(vec![("alice", u16::MAX), ("bob", u16::MAX)], vec![("carol", 10_000)])).unwrap_err();
+    assert_eq!(NeutronError::InvalidShareTotal { total: 141_070 }, err);
+
+    let err = submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission(vec![("Alice", 9000)], vec![("carol", 1000)])).unwrap_err();
+    assert_eq!(NeutronError::InvalidRecipient { recipient: "Alice".to_string() }, err);
+
+    // basis points allow splits finer than a percent
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission(vec![("alice", 9975)], vec![("carol", 25)])).unwrap();
+
+    CUSTODY_FUNDS.save(&mut deps.storage, custody_key(&Addr::unchecked("dave"), 0, "uatom", None), &CustodyFunds {
+        amount: Uint128::new(10_000),
+        proposal_id: 0,
+        locked: true,
+        remote: None,
+        application: Some(alice.clone()),
+        excess: Uint128::zero(),
+    }).unwrap();
+
+    let res: PayoutSimulation = from_binary(
+        &query_simulate_payout(deps.as_ref(), 0, "alice".to_string()).unwrap()
+    ).unwrap();
+    assert_eq!(vec![(alice, Uint128::new(9975)), (carol, Uint128::new(25))], res.legs.into_iter().map(|l| (l.recipient, l.amount)).collect::<Vec<_>>());
+}
+
+
+#[test]
+fn test_conflict_rules() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+
+    let submission = |applicant: &Addr, auditor: &Addr| ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: applicant.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: auditor.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    };
+
+    let err = submit_application(deps.as_mut(), env.clone(), bob.clone(), 0, submission(&alice, &alice)).unwrap_err();
+    assert_eq!(NeutronError::AuditorIsApplicant { auditor: "alice".to_string() }, err);
+
+    let err = submit_application(deps.as_mut(), env.clone(), bob.clone(), 0, submission(&alice, &bob)).unwrap_err();
+    assert_eq!(NeutronError::AuditorIsSender { auditor: "bob".to_string() }, err);
+
+    let err = update_conflict_rules(&mut deps.storage, bob.clone(), ConflictRules::default()).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized {}, err);
+
+    update_conflict_rules(&mut deps.storage, alice.clone(), ConflictRules { separate_auditors: true, max_auditor_funding_bps: Some(5000) }).unwrap();
+
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: carol.clone(), funds: coins(60, "uatom") }, 0, None, None).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: bob.clone(), funds: coins(40, "uatom") }, 0, None, None).unwrap();
+
+    let err = submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission(&alice, &carol)).unwrap_err();
+    assert_eq!(NeutronError::AuditorIsMajorFunder { auditor: "carol".to_string(), denom: "uatom".to_string() }, err);
+
+    // the rules are checked again when the auditor verifies
+    update_conflict_rules(&mut deps.storage, alice.clone(), ConflictRules { separate_auditors: true, max_auditor_funding_bps: None }).unwrap();
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission(&alice, &carol)).unwrap();
+    approve_application(&mut deps.storage, &bob, 0, alice.clone()).unwrap();
+    accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+
+    update_conflict_rules(&mut deps.storage, alice.clone(), ConflictRules { separate_auditors: true, max_auditor_funding_bps: Some(5000) }).unwrap();
+    deliver(&mut deps.storage, &alice, 0);
+    let err = verify_application(&mut deps.storage, carol.clone(), 0, alice.clone()).unwrap_err();
+    assert_eq!(NeutronError::AuditorIsMajorFunder { auditor: "carol".to_string(), denom: "uatom".to_string() }, err);
+
+    update_conflict_rules(&mut deps.storage, alice.clone(), ConflictRules { separate_auditors: true, max_auditor_funding_bps: Some(6000) }).unwrap();
+    verify_application(&mut deps.storage, carol.clone(), 0, alice.clone()).unwrap();
+}
+
+
+#[test]
+fn test_auditor_registry() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: bob.clone(), funds: coins(100, "uatom") }, 0, None, Some(true)).unwrap();
+
+    let submission = ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    };
+
+    let err = submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission.clone()).unwrap_err();
+    assert_eq!(NeutronError::AuditorNotRegistered { auditor: "carol".to_string() }, err);
+
+    let err = register_auditor(&mut deps.storage, fee_info(&carol, 500), "ipfs://carol".to_string()).unwrap_err();
+    assert_eq!(NeutronError::BondTooLow { required: "1000000untrn".to_string() }, err);
+    register_auditor(&mut deps.storage, fee_info(&carol, 1_000_000), "ipfs://carol".to_string()).unwrap();
+
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission).unwrap();
+    approve_application(&mut deps.storage, &bob, 0, alice.clone()).unwrap();
+    accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+
+    let err = raise_dispute(&mut deps.storage, bob.clone(), 0, alice.clone(), "Nothing delivered".to_string()).unwrap_err();
+    assert_eq!(NeutronError::CantDispute {}, err);
+
+    deliver(&mut deps.storage, &alice, 0);
+    verify_application(&mut deps.storage, carol.clone(), 0, alice.clone()).unwrap();
+
+    let err = raise_dispute(&mut deps.storage, dave.clone(), 0, alice.clone(), "Nothing delivered".to_string()).unwrap_err();
+    assert_eq!(NeutronError::CantDispute {}, err);
+    raise_dispute(&mut deps.storage, bob.clone(), 0, alice.clone(), "Nothing delivered".to_string()).unwrap();
+
+    let err = resolve_dispute(&mut deps.storage, bob.clone(), 0, true).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized {}, err);
+
+    // the slashed bond goes to the funder that backed the application
+    let res = resolve_dispute(&mut deps.storage, alice.clone(), 0, true).unwrap();
+    assert_eq!(vec![CosmosMsg::Bank(BankMsg::Send { to_address: "bob".to_string(), amount: coins(1_000_000, "untrn") })], res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>());
+    assert_eq!(Uint128::zero(), AUDITORS.load(&deps.storage, &carol).unwrap().bond);
+    assert_eq!(DisputeStatus::Upheld, DISPUTES.load(&deps.storage, 0).unwrap().status);
+
+    let err = resolve_dispute(&mut deps.storage, alice.clone(), 0, false).unwrap_err();
+    assert_eq!(NeutronError::DisputeNotOpen {}, err);
+
+    // bonds are returned only after the unbonding period
+    register_auditor(&mut deps.storage, fee_info(&dave, 2_000_000), "ipfs://dave".to_string()).unwrap();
+    unbond_auditor(&mut deps.storage, env.clone(), dave.clone()).unwrap();
+
+    let release_at = env.block.height + BondRules::default().unbonding_period;
+    let err = claim_auditor_bond(&mut deps.storage, env.clone(), dave.clone()).unwrap_err();
+    assert_eq!(NeutronError::BondLocked { release_at }, err);
+
+    env.block.height = release_at;
+    let res = claim_auditor_bond(&mut deps.storage, env, dave.clone()).unwrap();
+    assert_eq!(CosmosMsg::Bank(BankMsg::Send { to_address: "dave".to_string(), amount: coins(2_000_000, "untrn") }), res.messages[0].msg);
+    assert!(!AUDITORS.has(&deps.storage, &dave));
+}
+
+
+#[test]
+fn test_reputation() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+
+    let submission = |applicant: &Addr, env: &cosmwasm_std::Env| ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: applicant.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    };
+    let reputation = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, NeutronQuery>, address: &str| -> ReputationStats {
+        from_binary(&query_reputation(deps.as_ref(), address.to_string()).unwrap()).unwrap()
+    };
+
+    submit_proposal(&mut deps.storage, alice.clone(), "First".to_string(), "Description".to_string(), vec![]).unwrap();
+    submit_application(deps.as_mut(), env.clone(), bob.clone(), 0, submission(&bob, &env)).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: dave.clone(), funds: coins(100, "uatom") }, 0, None, None).unwrap();
+    approve_application(&mut deps.storage, &dave, 0, bob.clone()).unwrap();
+    accept_application(&mut deps.storage, bob.clone(), 0, bob.clone()).unwrap();
+    deliver(&mut deps.storage, &bob, 0);
+    verify_application(&mut deps.storage, carol.clone(), 0, bob.clone()).unwrap();
+
+    assert_eq!(ReputationStats { submitted: 1, accepted: 1, delivered: 1, expired: 0, disputed: 0, earned: coins(90, "uatom") }, reputation(&deps, "bob"));
+    assert_eq!(coins(10, "uatom"), reputation(&deps, "carol").earned);
+    assert_eq!(ReputationStats::default(), reputation(&deps, "dave"));
+
+    submit_proposal(&mut deps.storage, alice.clone(), "Second".to_string(), "Description".to_string(), vec![]).unwrap();
+    let requirement = ReputationRequirement { min_delivered: 1, max_expired: Some(0), max_disputed: None };
+
+    let err = set_min_reputation(&mut deps.storage, bob.clone(), 1, Some(requirement.clone())).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized {}, err);
+    set_min_reputation(&mut deps.storage, alice.clone(), 1, Some(requirement)).unwrap();
+
+    let err = submit_application(deps.as_mut(), env.clone(), dave.clone(), 1, submission(&dave, &env)).unwrap_err();
+    assert_eq!(NeutronError::ReputationTooLow { applicant: "dave".to_string() }, err);
+
+    submit_application(deps.as_mut(), env.clone(), bob.clone(), 1, submission(&bob, &env)).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: dave.clone(), funds: coins(50, "uosmo") }, 1, None, None).unwrap();
+    approve_application(&mut deps.storage, &dave, 1, bob.clone()).unwrap();
+    accept_application(&mut deps.storage, bob.clone(), 1, bob.clone()).unwrap();
+
+    let err = expire_application(&mut deps.storage, env.clone(), 1, bob.clone()).unwrap_err();
+    assert_eq!(NeutronError::NotExpirable {}, err);
+
+    // missing the deadline unlocks the funds and counts against the team
+    env.block.height += 1001;
+    expire_application(&mut deps.storage, env.clone(), 1, bob.clone()).unwrap();
+    assert!(!CUSTODY_FUNDS.load(&deps.storage, custody_key(&dave, 1, "uosmo", None)).unwrap().locked);
+    assert!(APPLICATION_FUNDING.may_load(&deps.storage, (1, bob.clone(), "uosmo")).unwrap().is_none());
+    assert_eq!(1, reputation(&deps, "bob").expired);
+
+    let err = verify_application(&mut deps.storage, carol.clone(), 1, bob.clone()).unwrap_err();
+    assert_eq!(NeutronError::ApplicationExpired {}, err);
+
+    let err = submit_application(deps.as_mut(), env.clone(), bob.clone(), 1, submission(&bob, &env)).unwrap_err();
+    assert_eq!(NeutronError::ReputationTooLow { applicant: "bob".to_string() }, err);
+
+    // funding too small to pay anyone doesn't count as a delivery
+    submit_proposal(&mut deps.storage, alice.clone(), "Third".to_string(), "Description".to_string(), vec![]).unwrap();
+    submit_application(deps.as_mut(), env.clone(), bob.clone(), 2, submission(&bob, &env)).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: dave.clone(), funds: coins(1, "uatom") }, 2, None, None).unwrap();
+    approve_application(&mut deps.storage, &dave, 2, bob.clone()).unwrap();
+    accept_application(&mut deps.storage, bob.clone(), 2, bob.clone()).unwrap();
+    deliver(&mut deps.storage, &bob, 2);
+    verify_application(&mut deps.storage, carol.clone(), 2, bob.clone()).unwrap();
+    assert_eq!(1, reputation(&deps, "bob").delivered);
+}
+
+
+#[test]
+fn test_deliverables() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    let submission = ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 5000 }, GoodFee { recipient: bob.clone(), share_bps: 4000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 500 }, GoodFee { recipient: dave.clone(), share_bps: 500 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    };
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission.clone()).unwrap();
+
+    let hash = "AB".repeat(32);
+
+    let err = submit_deliverable(&mut deps.storage, env.clone(), bob.clone(), 0, None, "ipfs://v1".to_string(), hash.clone()).unwrap_err();
+    assert_eq!(NeutronError::Std(StdError::generic_err("Application is not accepted")), err);
+
+    accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+
+    let err = verify_application(&mut deps.storage, carol.clone(), 0, alice.clone()).unwrap_err();
+    assert_eq!(NeutronError::DeliverableMissing {}, err);
+
+    let err = submit_deliverable(&mut deps.storage, env.clone(), carol.clone(), 0, Some(alice.clone()), "ipfs://v1".to_string(), hash.clone()).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized {}, err);
+
+    let err = submit_deliverable(&mut deps.storage, env.clone(), bob.clone(), 0, None, "ipfs://v1".to_string(), "abc".to_string()).unwrap_err();
+    assert!(matches!(err, NeutronError::InvalidDeliverable { .. }));
+
+    // any applicant can deliver and every version is kept
+    submit_deliverable(&mut deps.storage, env.clone(), bob.clone(), 0, None, "ipfs://v1".to_string(), hash.clone()).unwrap();
+    verify_application(&mut deps.storage, carol.clone(), 0, alice.clone()).unwrap();
+
+    // the application can't be submitted again once something was delivered
+    let err = submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, submission).unwrap_err();
+    assert_eq!(NeutronError::AlreadyDelivered {}, err);
+
+    // a new version needs to be verified again
+    env.block.time = env.block.time.plus_seconds(60);
+    let res = submit_deliverable(&mut deps.storage, env.clone(), alice.clone(), 0, None, "ipfs://v2".to_string(), "cd".repeat(32)).unwrap();
+    assert_eq!("1", event_attr(&res.events[0], "verifications_reset"));
+
+    let application: Application = from_binary(&query_application(deps.as_ref(), 0, "alice".to_string()).unwrap()).unwrap();
+    assert_eq!(vec!["ipfs://v1", "ipfs://v2"], application.deliverables.iter().map(|d| d.uri.as_str()).collect::<Vec<_>>());
+    assert_eq!("ab".repeat(32), application.deliverables[0].content_hash);
+    assert_eq!(bob, application.deliverables[0].submitted_by);
+    assert_eq!(env.block.time, application.deliverables[1].submitted_at);
+    assert!(application.verifications.is_empty());
+
+    verify_application(&mut deps.storage, carol.clone(), 0, alice.clone()).unwrap();
+    let res = verify_application(&mut deps.storage, dave, 0, alice.clone()).unwrap();
+    assert_eq!("true", event_attr(&res.events[0], "complete"));
+
+    let err = submit_deliverable(&mut deps.storage, env.clone(), alice, 0, None, "ipfs://v3".to_string(), hash).unwrap_err();
+    assert!(matches!(err, NeutronError::InvalidDeliverable { .. }));
+}
+
+
+#[test]
+fn test_progress_updates() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    }).unwrap();
+
+    let post = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, NeutronQuery>, sender: &Addr, summary: &str| {
+        post_update(&mut deps.storage, env.clone(), sender.clone(), 0, None, "ipfs://update".to_string(), summary.to_string())
+    };
+
+    let err = post(&mut deps, &alice, "Started").unwrap_err();
+    assert_eq!(NeutronError::Std(StdError::generic_err("Application is not accepted")), err);
+
+    accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+
+    let err = post_update(&mut deps.storage, env.clone(), bob.clone(), 0, Some(alice.clone()), "ipfs://update".to_string(), "Started".to_string()).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized {}, err);
+
+    let err = post(&mut deps, &alice, " ").unwrap_err();
+    assert!(matches!(err, NeutronError::InvalidUpdate { .. }));
+
+    for summary in ["Started", "Halfway", "Testing"] {
+        post(&mut deps, &alice, summary).unwrap();
+    }
+
+    let err = comment_update(&mut deps.storage, env.clone(), alice.clone(), 0, alice.clone(), 1, "Looks good".to_string()).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized {}, err);
+
+    let err = comment_update(&mut deps.storage, env.clone(), carol.clone(), 0, alice.clone(), 3, "Looks good".to_string());
+    assert!(err.is_err());
+
+    comment_update(&mut deps.storage, env.clone(), carol.clone(), 0, alice.clone(), 1, "Looks good".to_string()).unwrap();
+
+    let updates: Vec<(u64, ProgressUpdate)> = from_binary(&query_updates(deps.as_ref(), 0, "alice".to_string(), None, Some(2)).unwrap()).unwrap();
+    assert_eq!(vec![0, 1], updates.iter().map(|(id, _)| *id).collect::<Vec<_>>());
+    assert_eq!("Started", updates[0].1.summary);
+    assert_eq!(alice, updates[0].1.posted_by);
+    assert!(updates[0].1.comments.is_empty());
+    assert_eq!(carol, updates[1].1.comments[0].auditor);
+    assert_eq!("Looks good", updates[1].1.comments[0].comment);
+
+    let updates: Vec<(u64, ProgressUpdate)> = from_binary(&query_updates(deps.as_ref(), 0, "alice".to_string(), Some(1), None).unwrap()).unwrap();
+    assert_eq!(1, updates.len());
+    assert_eq!((2, "Testing"), (updates[0].0, updates[0].1.summary.as_str()));
+}
+
+
+#[test]
+fn test_withdrawn_funding() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+    let erin = Addr::unchecked("erin");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    }).unwrap();
+
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: dave.clone(), funds: coins(150, "uatom") }, 0, None, None).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: erin.clone(), funds: coins(200, "uatom") }, 0, Some(true), None).unwrap();
+
+    // withdrawn funds no longer count towards the funding of the proposal
+    withdraw_funds(&mut deps.storage, env.clone(), MessageInfo { sender: erin.clone(), funds: vec![] }).unwrap();
+    assert_eq!(Uint128::new(150), PROPOSAL_FUNDING.load(&deps.storage, (0, "uatom")).unwrap().amount);
+
+    // the approval is now the majority and the auto-agree funder has nothing left to agree with
+    approve_application(&mut deps.storage, &dave, 0, alice.clone()).unwrap();
+    let res = accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+    assert_eq!("true", event_attr(&res.events[0], "auto_agreed"));
+    let funding: Vec<(String, Uint128)> = from_binary(&query_application_funding(deps.as_ref(), 0, "alice".to_string()).unwrap()).unwrap();
+    assert_eq!(vec![("uatom".to_string(), Uint128::new(150))], funding);
+}
+
+
+#[test]
+fn test_application_budget() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+    let erin = Addr::unchecked("erin");
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+
+    let err = submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![coin(200, "uatom"), coin(0, "uosmo")],
+    }).unwrap_err();
+    assert_eq!(NeutronError::InvalidApplication {}, err);
+
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![coin(200, "uatom")],
+    }).unwrap();
+
+    for (funder, funds) in [(&bob, coins(300, "uatom")), (&dave, coins(100, "uatom")), (&erin, coins(50, "uosmo"))] {
+        fund_proposal_native(&mut deps.storage, MessageInfo { sender: funder.clone(), funds }, 0, None, None).unwrap();
+    }
+
+    // only the denoms of the budget can be approved
+    let err = approve_application(&mut deps.storage, &erin, 0, alice.clone()).unwrap_err();
+    assert_eq!(NeutronError::CantVote {}, err);
+
+    approve_application(&mut deps.storage, &dave, 0, alice.clone()).unwrap();
+    let err = accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap_err();
+    assert_eq!(NeutronError::BudgetNotCovered { budget: "200uatom".to_string() }, err);
+
+    approve_application(&mut deps.storage, &bob, 0, alice.clone()).unwrap();
+    accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+
+    let custody = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, NeutronQuery>, funder: &Addr| {
+        CUSTODY_FUNDS.load(&deps.storage, custody_key(funder, 0, "uatom", None)).unwrap()
+    };
+    assert_eq!((Uint128::new(150), Uint128::new(150)), (custody(&deps, &bob).locked_amount(), custody(&deps, &bob).excess));
+    assert_eq!((Uint128::new(50), Uint128::new(50)), (custody(&deps, &dave).locked_amount(), custody(&deps, &dave).excess));
+
+    let res = withdraw_funds(&mut deps.storage, env.clone(), MessageInfo { sender: dave.clone(), funds: vec![] }).unwrap();
+    assert_eq!(vec![CosmosMsg::Bank(BankMsg::Send { to_address: "dave".to_string(), amount: coins(50, "uatom") })],
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>());
+    assert_eq!((Uint128::new(50), Uint128::zero()), (custody(&deps, &dave).amount, custody(&deps, &dave).excess));
+    assert!(custody(&deps, &dave).locked);
+
+    let err = withdraw_funds(&mut deps.storage, env.clone(), MessageInfo { sender: dave.clone(), funds: vec![] }).unwrap_err();
+    assert_eq!(NeutronError::NoFunds {}, err);
+
+    deliver(&mut deps.storage, &alice, 0);
+    verify_application(&mut deps.storage, carol.clone(), 0, alice.clone()).unwrap();
+
+    // exactly the budget is paid and the rest of the approved funds is unlocked
+    assert_eq!(Uint128::new(180), custody(&deps, &alice).amount);
+    assert_eq!(Uint128::new(20), custody(&deps, &carol).amount);
+    assert_eq!((Uint128::new(150), false), (custody(&deps, &bob).amount, custody(&deps, &bob).locked));
+    assert!(CUSTODY_FUNDS.may_load(&deps.storage, custody_key(&dave, 0, "uatom", None)).unwrap().is_none());
+
+    // denoms approved before the budget was known are unlocked once the work starts
+    let frank = Addr::unchecked("frank");
+    let gina = Addr::unchecked("gina");
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: gina.clone(), funds: vec![coin(100, "uatom"), coin(40, "uosmo")] }, 1, None, None).unwrap();
+    approve_application(&mut deps.storage, &gina, 1, frank.clone()).unwrap();
+    submit_application(deps.as_mut(), env.clone(), frank.clone(), 1, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: frank.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 1000 }],
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![coin(100, "uatom")],
+    }).unwrap();
+    accept_application(&mut deps.storage, frank.clone(), 1, frank.clone()).unwrap();
+
+    let funding: Vec<(String, Uint128)> = from_binary(&query_application_funding(deps.as_ref(), 1, "frank".to_string()).unwrap()).unwrap();
+    assert_eq!(vec![("uatom".to_string(), Uint128::new(100))], funding);
+    let res = withdraw_funds(&mut deps.storage, env.clone(), MessageInfo { sender: gina.clone(), funds: vec![] }).unwrap();
+    assert_eq!(vec![CosmosMsg::Bank(BankMsg::Send { to_address: "gina".to_string(), amount: coins(40, "uosmo") })],
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>());
+    assert!(CUSTODY_FUNDS.load(&deps.storage, custody_key(&gina, 1, "uatom", None)).unwrap().locked);
+}
+
+
+#[test]
+fn test_competitive_selection() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let creator = Addr::unchecked("creator");
+    let auditor = Addr::unchecked("auditor");
+    let (alice, bob, frank) = (Addr::unchecked("alice"), Addr::unchecked("bob"), Addr::unchecked("frank"));
+    let (dave, erin, gina) = (Addr::unchecked("dave"), Addr::unchecked("erin"), Addr::unchecked("gina"));
+    let ends = cw_utils::Expiration::AtHeight(env.block.height + 100);
+    let deliver_by = cw_utils::Expiration::AtHeight(env.block.height + 1000);
+
+    CONFIG.save(&mut deps.storage, &mock_config(&creator, 0)).unwrap();
+    submit_proposal(&mut deps.storage, creator.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+
+    let err = open_selection(&mut deps.storage, env.clone(), alice.clone(), 0, ends, 2).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized {}, err);
+    let err = open_selection(&mut deps.storage, env.clone(), creator.clone(), 0, ends, 0).unwrap_err();
+    assert!(matches!(err, NeutronError::InvalidSelection { .. }));
+    open_selection(&mut deps.storage, env.clone(), creator.clone(), 0, ends, 2).unwrap();
+
+    let submission = |budget: u128| ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: Addr::unchecked("team"), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: auditor.clone(), share_bps: 1000 }],
+        deliver_by,
+        budget: coins(budget, "uatom"),
+    };
+
+    // bob is the second by approvals but his budget doesn't fit next to the one of alice
+    for (applicant, funder, budget, funds) in [(&alice, &dave, 100, 120), (&bob, &erin, 200, 100), (&frank, &gina, 150, 60)] {
+        submit_application(deps.as_mut(), env.clone(), applicant.clone(), 0, submission(budget)).unwrap();
+        fund_proposal_native(&mut deps.storage, MessageInfo { sender: funder.clone(), funds: coins(funds, "uatom") }, 0, None, None).unwrap();
+        approve_application(&mut deps.storage, funder, 0, applicant.clone()).unwrap();
+    }
+
+    let err = accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap_err();
+    assert_eq!(NeutronError::SelectionPending {}, err);
+    let err = select_applications(&mut deps.storage, env.clone(), 0).unwrap_err();
+    assert_eq!(NeutronError::SelectionPending {}, err);
+
+    env.block.height += 100;
+    let err = submit_application(deps.as_mut(), env.clone(), creator.clone(), 0, submission(10)).unwrap_err();
+    assert_eq!(NeutronError::SelectionClosed {}, err);
+
+    let res = select_applications(&mut deps.storage, env.clone(), 0).unwrap();
+    assert_eq!(res.events[0].attributes[1].value, "alice,frank");
+
+    let proposal = PROPOSALS.load(&deps.storage, 0).unwrap();
+    assert_eq!(Some(vec![alice.clone(), frank.clone()]), proposal.selection.unwrap().selected);
+
+    // approvals of bob are released and can't be made again
+    let erin_funds = CUSTODY_FUNDS.load(&deps.storage, custody_key(&erin, 0, "uatom", None)).unwrap();
+    assert_eq!((false, None), (erin_funds.locked, erin_funds.application));
+    let err = approve_application(&mut deps.storage, &erin, 0, bob.clone()).unwrap_err();
+    assert_eq!(NeutronError::NotSelected {}, err);
+    let err = accept_application(&mut deps.storage, bob.clone(), 0, bob.clone()).unwrap_err();
+    assert_eq!(NeutronError::NotSelected {}, err);
+
+    approve_application(&mut deps.storage, &erin, 0, frank.clone()).unwrap();
+    accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+    accept_application(&mut deps.storage, frank.clone(), 0, frank.clone()).unwrap();
+
+    let err = select_applications(&mut deps.storage, env.clone(), 0).unwrap_err();
+    assert_eq!(NeutronError::SelectionClosed {}, err);
+
+    // the proposal stays open until every winner delivered or missed the deadline
+    submit_deliverable(&mut deps.storage, env.clone(), Addr::unchecked("team"), 0, Some(alice.clone()), "ipfs://work".to_string(), "ab".repeat(32)).unwrap();
+    let res = verify_application(&mut deps.storage, auditor.clone(), 0, alice.clone()).unwrap();
+    assert!(!res.events.iter().any(|e| e.ty == "crossfund-proposal-completed"));
+    assert_eq!(ProposalStatus::Open, PROPOSALS.load(&deps.storage, 0).unwrap().status);
+
+    env.block.height += 1000;
+    let res = expire_application(&mut deps.storage, env.clone(), 0, frank.clone()).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "crossfund-proposal-completed"));
+    assert_eq!(ProposalStatus::Completed, PROPOSALS.load(&deps.storage, 0).unwrap().status);
+
+    // the number of applications a selection has to rank is capped
+    submit_proposal(&mut deps.storage, creator.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    let ends = cw_utils::Expiration::AtHeight(env.block.height + 100);
+    let deliver_by = cw_utils::Expiration::AtHeight(env.block.height + 1000);
+    let submission = ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: Addr::unchecked("team"), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: auditor.clone(), share_bps: 1000 }],
+        deliver_by,
+        budget: vec![],
+    };
+    for i in 0..=MAX_SELECTION_APPLICATIONS {
+        submit_application(deps.as_mut(), env.clone(), Addr::unchecked(format!("applicant{}", i)), 1, submission.clone()).unwrap();
+    }
+    let err = open_selection(&mut deps.storage, env.clone(), creator.clone(), 1, ends, 2).unwrap_err();
+    assert!(matches!(err, NeutronError::InvalidSelection { .. }));
+
+    APPLICATIONS.remove(&mut deps.storage, (1, Addr::unchecked("applicant0")));
+    open_selection(&mut deps.storage, env.clone(), creator.clone(), 1, ends, 2).unwrap();
+    let err = submit_application(deps.as_mut(), env.clone(), Addr::unchecked("applicant0"), 1, submission.clone()).unwrap_err();
+    assert!(matches!(err, NeutronError::InvalidSelection { .. }));
+    // the applications already competing can still be resubmitted
+    submit_application(deps.as_mut(), env, Addr::unchecked("applicant1"), 1, submission).unwrap();
+}
+
+
+#[test]
+fn test_bounty() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let creator = Addr::unchecked("creator");
+    let auditor = Addr::unchecked("auditor");
+    let (alice, bob, dave) = (Addr::unchecked("alice"), Addr::unchecked("bob"), Addr::unchecked("dave"));
+
+    CONFIG.save(&mut deps.storage, &mock_config(&creator, 0)).unwrap();
+    submit_proposal(&mut deps.storage, creator.clone(), "Bug bounty".to_string(), "Description".to_string(), vec![]).unwrap();
+
+    let auditors = |share_bps: u16| vec![GoodFee { recipient: auditor.clone(), share_bps }];
+
+    let err = open_bounty(deps.as_mut(), alice.clone(), 0, auditors(1000)).unwrap_err();
+    assert_eq!(NeutronError::NonAuthorized {}, err);
+    let err = open_bounty(deps.as_mut(), creator.clone(), 0, auditors(10000)).unwrap_err();
+    assert_eq!(NeutronError::InvalidShareTotal { total: 10000 }, err);
+    let erin = Addr::unchecked("erin");
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: erin.clone(), funds: coins(500, "uatom") }, 0, None, None).unwrap();
+    open_bounty(deps.as_mut(), creator.clone(), 0, auditors(1000)).unwrap();
+
+    fund_proposal_native(&mut deps.storage, MessageInfo { sender: dave.clone(), funds: coins(1000, "uatom") }, 0, None, None).unwrap();
+
+    // the pool stays locked for the hunters, whether it was funded before or after the bounty opened
+    for funder in [&erin, &dave] {
+        let err = withdraw_funds(&mut deps.storage, env.clone(), MessageInfo { sender: funder.clone(), funds: vec![] }).unwrap_err();
+        assert_eq!(NeutronError::NoFunds {}, err);
+    }
+
+    let err = submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 9000 }],
+        auditors: auditors(1000),
+        deliver_by: cw_utils::Expiration::AtHeight(env.block.height + 1000),
+        budget: vec![],
+    }).unwrap_err();
+    assert!(matches!(err, NeutronError::InvalidBounty { .. }));
+    let err = approve_application(&mut deps.storage, &dave, 0, alice.clone()).unwrap_err();
+    assert!(matches!(err, NeutronError::InvalidBounty { .. }));
+
+    // hunters deliver without an application or an acceptance
+    deliver(&mut deps.storage, &alice, 0);
+    deliver(&mut deps.storage, &bob, 0);
+
+    let application: Application = from_binary(&query_application(deps.as_ref(), 0, "bob".to_string()).unwrap()).unwrap();
+    assert!(application.accepted);
+    assert_eq!(vec![GoodFee { recipient: bob.clone(), share_bps: 9000 }], application.applicants);
+
+    let err = submit_deliverable(&mut deps.storage, env.clone(), auditor.clone(), 0, None, "ipfs://work".to_string(), "ab".repeat(32)).unwrap_err();
+    assert_eq!(NeutronError::AuditorIsSender { auditor: "auditor".to_string() }, err);
+
+    let res = verify_application(&mut deps.storage, auditor.clone(), 0, bob.clone()).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "crossfund-bounty-closed"));
+
+    assert_eq!(Uint128::new(1350), CUSTODY_FUNDS.load(&deps.storage, custody_key(&bob, 0, "uatom", None)).unwrap().amount);
+    assert_eq!(Uint128::new(150), CUSTODY_FUNDS.load(&deps.storage, custody_key(&auditor, 0, "uatom", None)).unwrap().amount);
+    assert!(CUSTODY_FUNDS.may_load(&deps.storage, custody_key(&dave, 0, "uatom", None)).unwrap().is_none());
+
+    // the funders of the pool back the winner
+    for funder in [&erin, &dave] {
+        assert_eq!(Some(false), APPLICATION_BACKERS.may_load(&deps.storage, (0, &bob, funder)).unwrap());
+    }
+
+    // later submissions are closed
+    let err = verify_application(&mut deps.storage, auditor.clone(), 0, alice.clone()).unwrap_err();
+    assert_eq!(NeutronError::BountyClosed {}, err);
+    let err = submit_deliverable(&mut deps.storage, env, alice, 0, None, "ipfs://work".to_string(), "ab".repeat(32)).unwrap_err();
+    assert_eq!(NeutronError::BountyClosed {}, err);
+}
+
+
+#[test]
+fn test_deadline_extension() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let dave = Addr::unchecked("dave");
+    let erin = Addr::unchecked("erin");
+    let deadline = cw_utils::Expiration::AtHeight(env.block.height + 1000);
+    let extended = cw_utils::Expiration::AtHeight(env.block.height + 2000);
+
+    CONFIG.save(&mut deps.storage, &mock_config(&alice, 0)).unwrap();
+    submit_proposal(&mut deps.storage, alice.clone(), "Test".to_string(), "Description".to_string(), vec![]).unwrap();
+    submit_application(deps.as_mut(), env.clone(), alice.clone(), 0, ApplicationSubmission {
+        applicants: vec![GoodFee { recipient: alice.clone(), share_bps: 9000 }],
+        auditors: vec![GoodFee { recipient: carol.clone(), share_bps: 1000 }],
+        deliver_by: deadline,
+        budget: vec![],
+    }).unwrap();
+
+    for (funder, funds) in [(&bob, coins(60, "uatom")), (&dave, coins(40, "uatom")), (&erin, coins(100, "uosmo"))] {
+        fund_proposal_native(&mut deps.storage, MessageInfo { sender: funder.clone(), funds }, 0, None, None).unwrap();
+        approve_application(&mut deps.storage, funder, 0, alice.clone()).unwrap();
+    }
+    accept_application(&mut deps.storage, alice.clone(), 0, alice.clone()).unwrap();
+
+    let request = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, NeutronQuery>, sender: &Addr, deliver_by: &cw_utils::Expiration, env: &cosmwasm_std::Env| {
+        request_extension(&mut deps.storage, env.clone(), sender.clone(), 0, None, *deliver_by, "Audit took longer".to_string())
+    };
+    let open_request = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, NeutronQuery>, env: &cosmwasm_std::Env| -> Option<ExtensionRequest> {
+        from_binary(&query_extension_request(deps.as_ref(), env.clone(), 0, "alice".to_string()).unwrap()).unwrap()
+    };
+
+    assert_eq!(NeutronError::NonAuthorized {}, request(&mut deps, &carol, &extended, &env).unwrap_err());
+    assert!(matches!(request(&mut deps, &alice, &deadline, &env).unwrap_err(), NeutronError::InvalidExtension { .. }));
+
+    request(&mut deps, &alice, &extended, &env).unwrap();
+    assert_eq!(NeutronError::ExtensionPending {}, request(&mut deps, &alice, &extended, &env).unwrap_err());
+
+    let err = vote_extension(&mut deps.storage, env.clone(), carol.clone(), 0, alice.clone(), true).unwrap_err();
+    assert_eq!(NeutronError::CantVote {}, err);
+
+    // every denom counts for half of the weight
+    vote_extension(&mut deps.storage, env.clone(), bob.clone(), 0, alice.clone(), true).unwrap();
+    vote_extension(&mut deps.storage, env.clone(), dave.clone(), 0, alice.clone(), false).unwrap();
+    let open = open_request(&deps, &env).unwrap();
+    assert_eq!((Decimal::percent(30), Decimal::percent(20)), (open.votes_for, open.votes_against));
+
+    vote_extension(&mut deps.storage, env.clone(), erin.clone(), 0, alice.clone(), true).unwrap();
+    assert_eq!(None, open_request(&deps, &env));
+    assert_eq!(extended, APPLICATIONS.load(&deps.storage, (0, alice.clone())).unwrap().deliver_by);
+
+    // rejection keeps the deadline
+    let further = cw_utils::Expiration::AtHeight(env.block.height + 3000);
+    request(&mut deps, &alice, &further, &env).unwrap();
+    vote_extension(&mut deps.storage, env.clone(), erin.clone(), 0, alice.clone(), false).unwrap();
+    assert_eq!(None, open_request(&deps, &env));
+    assert_eq!(extended, APPLICATIONS.load(&deps.storage, (0, alice.clone())).unwrap().deliver_by);
+
+    // so does silence
+    request(&mut deps, &alice, &further, &env).unwrap();
+    env.block.height += EXTENSION_VOTING_PERIOD + 1;
+    assert_eq!(None, open_request(&deps, &env));
+    let err = vote_extension(&mut deps.storage, env, bob, 0, alice.clone(), true).unwrap_err();
+    assert_eq!(NeutronError::NoExtensionRequest {}, err);
+    assert_eq!(extended, APPLICATIONS.load(&deps.storage, (0, alice)).unwrap().deliver_by);
+}
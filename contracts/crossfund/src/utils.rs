@@ -1,22 +1,118 @@
 use std::{hash::{Hash, Hasher}, collections::hash_map::DefaultHasher};
-use cosmwasm_std::{BlockInfo};
+use cosmwasm_std::{coin, Addr, Api, BlockInfo, Coin, Event, StdError, StdResult, Uint128};
 use cw_utils::Expiration;
-use neutron_sdk::bindings::msg::IbcFee;
-use crate::{storage::{Application, GoodFee}, msg::ApplicationSubmission};
+use neutron_sdk::{bindings::msg::IbcFee, NeutronError, NeutronResult};
+use crate::{storage::{Application, CustodyFunds, GoodFee, RETRY_BASE_DELAY, RETRY_MAX_DELAY, TOTAL_SHARE_BPS}, msg::{ApplicationSubmission, PayoutLeg}};
 
-const FEE_DENOM: &str = "untrn";
+pub const FEE_DENOM: &str = "untrn";
+pub const LOCAL_PREFIX: &str = "neutron";
 
-pub fn valid_application(
-    application: &ApplicationSubmission,
+
+/// Event of the contract, the chain reports it with the `wasm-` prefix e.g. `wasm-crossfund-proposal-created`
+pub fn crossfund_event(action: &str) -> Event {
+    Event::new(format!("crossfund-{}", action))
+}
+
+
+/// Funding of a proposal, the source and the origin of remote funds are added by the caller
+pub fn funded_event(
+    proposal_id: u64,
+    funder: &str,
+    denom: &str,
+    amount: Uint128,
+    auto_agree: bool,
+) -> Event {
+    crossfund_event("proposal-funded")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("funder", funder)
+        .add_attribute("denom", denom)
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("auto_agree", auto_agree.to_string())
+}
+
+
+/// Checks the fields of an application and the shares of its participants.
+/// Recipients must be local addresses or bech32 addresses on a remote chain
+pub fn validate_application(
+    api: &dyn Api,
+    application: &mut ApplicationSubmission,
     block_info: &BlockInfo,
-) -> bool {
+) -> NeutronResult<()> {
     let sh =  shareholders_sub(application);
-    sh.len() < 100 && 
-    application.deliver_by != Expiration::Never {} &&
-    !application.deliver_by.is_expired(&block_info) &&
-    application.applicants.len() > 0 &&
-    application.auditors.len() > 0 &&
-    sh.iter().map(|f| f.percent_share).sum::<u8>() == 100
+
+    if sh.len() >= 100 ||
+        application.deliver_by == (Expiration::Never {}) ||
+        application.deliver_by.is_expired(block_info) ||
+        application.applicants.is_empty() ||
+        application.auditors.is_empty() {
+        return Err(NeutronError::InvalidApplication);
+    }
+
+    for (i, c) in application.budget.iter().enumerate() {
+        if c.amount.is_zero() || application.budget[..i].iter().any(|other| other.denom == c.denom) {
+            return Err(NeutronError::InvalidApplication);
+        }
+    }
+
+    let total = validate_shareholders(api, &mut application.applicants)? + validate_shareholders(api, &mut application.auditors)?;
+
+    if total != TOTAL_SHARE_BPS {
+        return Err(NeutronError::InvalidShareTotal { total });
+    }
+
+    Ok(())
+}
+
+
+/// Checks a group of shareholders of an application and returns the sum of their shares.
+/// Remote addresses are stored in lowercase so that a recipient can't be listed twice in different cases
+pub fn validate_shareholders(
+    api: &dyn Api,
+    group: &mut [GoodFee],
+) -> NeutronResult<u32> {
+    let mut total: u32 = 0;
+
+    for i in 0..group.len() {
+        group[i].recipient = validate_recipient(api, &group[i].recipient)?;
+        let gf = &group[i];
+        if group[..i].iter().any(|other| other.recipient == gf.recipient) {
+            return Err(NeutronError::DuplicateShareholder { recipient: gf.recipient.to_string() });
+        }
+        if gf.share_bps == 0 {
+            return Err(NeutronError::ZeroShare { recipient: gf.recipient.to_string() });
+        }
+        // a group has less than 100 members so the sum can't overflow
+        total += u32::from(gf.share_bps);
+    }
+
+    Ok(total)
+}
+
+
+/// Local recipients must be valid addresses of the chain, others only need to be bech32 encoded
+pub fn validate_recipient(api: &dyn Api, recipient: &Addr) -> NeutronResult<Addr> {
+    if let Ok(addr) = api.addr_validate(recipient.as_str()) {
+        return Ok(addr);
+    }
+    match bech32::decode(recipient.as_str()) {
+        Ok((hrp, _, _)) if hrp != LOCAL_PREFIX => Ok(Addr::unchecked(recipient.as_str().to_lowercase())),
+        _ => Err(NeutronError::InvalidRecipient { recipient: recipient.to_string() }),
+    }
+}
+
+
+/// Recipients that were validated as bech32 addresses of another chain
+pub fn is_remote_recipient(recipient: &Addr) -> bool {
+    matches!(bech32::decode(recipient.as_str()), Ok((hrp, _, _)) if hrp != LOCAL_PREFIX)
+}
+
+
+/// Address of the same account on Neutron
+pub fn local_address(address: &str) -> StdResult<Addr> {
+    let (_, data, variant) = bech32::decode(address).map_err(|e| StdError::generic_err(e.to_string()))?;
+    bech32::encode(LOCAL_PREFIX, data, variant)
+        .map(Addr::unchecked)
+        .map_err(|e| StdError::generic_err(e.to_string()))
 }
 
 
@@ -33,9 +129,98 @@ pub fn shareholders(
 }
 
 
+/// Adds a deposit to the custody funds of a funder for a proposal.
+/// Funds already locked for an application keep the deposit as excess so that it stays withdrawable
+pub fn add_custody(
+    funds: Option<CustodyFunds>,
+    amount: Uint128,
+    proposal_id: u64,
+    remote: Option<String>,
+) -> CustodyFunds {
+    match funds {
+        Some(mut funds) => {
+            funds.amount += amount;
+            if funds.locked {
+                funds.excess += amount;
+            }
+            funds
+        },
+        None => CustodyFunds {
+            amount,
+            proposal_id,
+            locked: false,
+            remote,
+            application: None,
+            excess: Uint128::zero(),
+        },
+    }
+}
+
+
+/// Locks funds in the pool of an open bounty, nothing can be withdrawn until a submission wins it
+pub fn lock_pool(funds: CustodyFunds) -> CustodyFunds {
+    CustodyFunds {
+        locked: true,
+        excess: Uint128::zero(),
+        ..funds
+    }
+}
+
+
+/// Splits the locked funds between the shareholders of the application.
+/// Returns the legs per recipient and denom and the dust left by rounding the shares down
+pub fn payout_legs(
+    application: &Application,
+    funds: &[((Addr, String), CustodyFunds)],
+) -> (Vec<PayoutLeg>, Vec<Coin>) {
+
+    // remote funds stay in their interchain account so the sums are kept per denom and account
+    let mut sums: Vec<(String, Uint128, Option<String>)> = Vec::with_capacity(10);
+
+    for ((_, token), fund) in funds {
+        if let Some(index) = sums.iter().position(|(t, _, r)| t == token && *r == fund.remote) {
+            // Add the fund amount to the existing sum
+            sums[index].1 += fund.locked_amount();
+        } else {
+            // Add a new sum entry for the token
+            sums.push((token.clone(), fund.locked_amount(), fund.remote.clone()));
+        }
+    }
+
+    let mut legs = Vec::new();
+    let mut dust = Vec::new();
+
+    for (token, total, remote) in &sums {
+        let mut paid = Uint128::zero();
+        for gf in shareholders(application) {
+            let amount: Uint128 = total.multiply_ratio(gf.share_bps, TOTAL_SHARE_BPS);
+            paid += amount;
+            legs.push(PayoutLeg {
+                recipient: gf.recipient,
+                denom: token.clone(),
+                amount,
+                remote: remote.clone(),
+            });
+        }
+        if *total > paid {
+            match dust.iter_mut().find(|c: &&mut Coin| c.denom == *token) {
+                Some(c) => c.amount += *total - paid,
+                None => dust.push(coin((*total - paid).u128(), token)),
+            }
+        }
+    }
+
+    (legs, dust)
+}
+
+
 pub fn min_ntrn_ibc_fee(fee: IbcFee) -> IbcFee {
     IbcFee {
-        recv_fee: fee.recv_fee,
+        recv_fee: fee
+            .recv_fee
+            .into_iter()
+            .filter(|a| a.denom == FEE_DENOM)
+            .collect(),
         ack_fee: fee
             .ack_fee
             .into_iter()
@@ -50,6 +235,46 @@ pub fn min_ntrn_ibc_fee(fee: IbcFee) -> IbcFee {
 }
 
 
+/// Sum of the coins in the fee denom
+pub fn fee_denom_amount(coins: &[Coin]) -> Uint128 {
+    coins
+        .iter()
+        .filter(|c| c.denom == FEE_DENOM)
+        .map(|c| c.amount)
+        .sum()
+}
+
+
+/// Total amount of the fee denom escrowed by the fee module for a packet
+pub fn total_ibc_fee(fee: &IbcFee) -> Uint128 {
+    fee_denom_amount(&fee.recv_fee) + fee_denom_amount(&fee.ack_fee) + fee_denom_amount(&fee.timeout_fee)
+}
+
+
+/// Blocks to wait before retrying an operation that failed the given number of times
+pub fn retry_delay(attempts: u32) -> u64 {
+    RETRY_BASE_DELAY
+        .saturating_mul(1u64 << attempts.saturating_sub(1).min(16))
+        .min(RETRY_MAX_DELAY)
+}
+
+
+/// Interchain account id of a proposal on the given connection
+pub fn ica_id(proposal_id: u64, connection_id: &str) -> String {
+    format!("{}-{}", proposal_id, connection_id)
+}
+
+
+/// Interchain account id is the part of the controller port after the owner delimiter
+pub fn ica_id_from_port(port_id: &str) -> String {
+    port_id
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+
 pub fn hash_data<T>(data: &T) -> u64
 where
     T: Hash,
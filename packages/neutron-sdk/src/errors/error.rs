@@ -54,11 +54,125 @@ pub enum NeutronError {
     #[error("You are not an auditor for this application")]
     NonAuthorized,
 
-    #[error("Invalid application. Missing / invalid fields or sum of shares not equal tp 100")]
+    #[error("Invalid application. Missing / invalid fields or sum of shares not equal to 10000 basis points")]
     InvalidApplication,
 
+    #[error("Shares must add up to 10000 basis points, got {total}")]
+    InvalidShareTotal { total: u32 },
+
+    #[error("Shareholder {recipient} has a zero share")]
+    ZeroShare { recipient: String },
+
+    #[error("Shareholder {recipient} is listed more than once")]
+    DuplicateShareholder { recipient: String },
+
+    #[error("Invalid shareholder address {recipient}")]
+    InvalidRecipient { recipient: String },
+
+    #[error("Auditor {auditor} can't be an applicant of the application")]
+    AuditorIsApplicant { auditor: String },
+
+    #[error("Auditor {auditor} can't audit their own application")]
+    AuditorIsSender { auditor: String },
+
+    #[error("Auditor {auditor} funded too large a share of the proposal in {denom}")]
+    AuditorIsMajorFunder { auditor: String, denom: String },
+
+    #[error("Proposal requires registered auditors, {auditor} isn't one")]
+    AuditorNotRegistered { auditor: String },
+
+    #[error("Auditor bond must be at least {required}")]
+    BondTooLow { required: String },
+
+    #[error("Auditor is unbonding")]
+    AuditorUnbonding,
+
+    #[error("Auditor bond can be claimed from height {release_at}")]
+    BondLocked { release_at: u64 },
+
+    #[error("Only funders backing a verified application can dispute it")]
+    CantDispute,
+
+    #[error("Verifications of the application are already disputed in dispute {id}")]
+    DisputeExists { id: u64 },
+
+    #[error("Dispute is already resolved")]
+    DisputeNotOpen,
+
+    #[error("Applicant {applicant} doesn't meet the reputation required by the proposal")]
+    ReputationTooLow { applicant: String },
+
+    #[error("Application has expired")]
+    ApplicationExpired,
+
+    #[error("Application has already been accepted")]
+    AlreadyAccepted,
+
+    #[error("Application can't expire before its deadline or after delivery")]
+    NotExpirable,
+
+    #[error("Nothing has been delivered for the application yet")]
+    DeliverableMissing,
+
+    #[error("Work has been delivered for the application, it can't be submitted again")]
+    AlreadyDelivered,
+
+    #[error("Invalid deliverable: {reason}")]
+    InvalidDeliverable { reason: String },
+
+    #[error("Approved funds don't cover the budget of {budget}")]
+    BudgetNotCovered { budget: String },
+
+    #[error("Invalid bounty: {reason}")]
+    InvalidBounty { reason: String },
+
+    #[error("Bounty has already been paid out")]
+    BountyClosed,
+
+    #[error("Invalid selection: {reason}")]
+    InvalidSelection { reason: String },
+
+    #[error("Applications are still being selected")]
+    SelectionPending,
+
+    #[error("Selection of the applications is over")]
+    SelectionClosed,
+
+    #[error("Application hasn't been selected")]
+    NotSelected,
+
+    #[error("Invalid update: {reason}")]
+    InvalidUpdate { reason: String },
+
+    #[error("Invalid extension: {reason}")]
+    InvalidExtension { reason: String },
+
+    #[error("Extension request is already open for the application")]
+    ExtensionPending,
+
+    #[error("No open extension request for the application")]
+    NoExtensionRequest,
+
     #[error("No funds to withdraw or they are locked")]
-    NoFunds
+    NoFunds,
+
+    #[error("Refund of the deposit is already in progress")]
+    RefundInProgress,
+
+    #[error("Interchain account for the proposal is already registered on this connection")]
+    IcaAlreadyRegistered,
+
+    #[error("Interchain account channel is not open, register the account again")]
+    IcaNotOpen,
+
+    #[error("Attached funds don't cover the fees, required {required}")]
+    InsufficientFunds { required: String },
+
+    #[error("Interchain operation is still waiting for the acknowledgement")]
+    OperationInFlight,
+
+    #[error("Interchain operation can be retried from block {retry_after}")]
+    RetryNotDue { retry_after: u64 },
 }
 
 impl From<serde_json_wasm::de::Error> for NeutronError {
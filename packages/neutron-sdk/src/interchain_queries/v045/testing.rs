@@ -130,7 +130,7 @@ fn test_bank_total_supply_reconstruct() {
             let s = StorageValue {
                 storage_prefix: "".to_string(),
                 key: Binary(denom_key),
-                value: Binary(case.amount.as_str().as_bytes().to_vec()),
+                value: Binary(case.amount.as_bytes().to_vec()),
             };
             st_values.push(s);
         }
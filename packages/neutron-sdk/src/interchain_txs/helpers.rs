@@ -36,11 +36,12 @@ pub fn get_port_id<R: AsRef<str>>(contract_address: R, interchain_account_id: R)
 }
 
 pub fn get_proposal_id(port_id: &str) -> u64 {
-    // split by ICA_OWNER_DELIMITER and return the last element
+    // split by ICA_OWNER_DELIMITER and take the proposal part of the last element
+    // which is either `{proposal_id}` or `{proposal_id}-{connection_id}`
     port_id
-        .split(ICA_OWNER_DELIMITER)
-        .last()
-        .unwrap()
-        .parse::<u64>()
+        .rsplit(ICA_OWNER_DELIMITER)
+        .next()
+        .and_then(|ica_id| ica_id.split('-').next())
+        .and_then(|id| id.parse::<u64>().ok())
         .unwrap_or(0)
 }
\ No newline at end of file
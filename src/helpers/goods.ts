@@ -1,5 +1,6 @@
+import { cosmos } from '@cosmos-client/core/cjs/proto';
 import { CosmosWrapper, WalletWrapper } from "./cosmos";
-import { Addr, AllProposalInfo, CustodyFunds, Expiration, ProposalInfo, ShareHolder } from "./types";
+import { Addr, AllProposalInfo, CustodyFunds, Expiration, ProgressUpdate, ProposalInfo, ShareHolder } from "./types";
 import { getWithAttempts } from "./wait";
 
 
@@ -9,6 +10,7 @@ export const createProposal = (
   contractAddress: string,
   title?: string,
   description?: string,
+  tags?: string[],
 ) => {
   return cm.executeContract(
     contractAddress,
@@ -16,6 +18,7 @@ export const createProposal = (
       submit_proposal: {
         title: title ?? 'test proposal',
         description:  description ?? 'test proposal description',
+        tags,
       }
     }),
   );
@@ -28,6 +31,7 @@ export const fundProposal = (
   amount: string,
   denom: string,
   auto_agree?: boolean,
+  require_registered_auditors?: boolean,
 ) => {
   return cm.executeContract(
     contractAddress,
@@ -35,6 +39,7 @@ export const fundProposal = (
       fund_proposal: {
         proposal_id,
         auto_agree,
+        require_registered_auditors,
       }
     }),
     [{ amount, denom }],
@@ -42,6 +47,24 @@ export const fundProposal = (
 }
 
 
+export const registerAuditor = (
+  cm: WalletWrapper,
+  contractAddress: string,
+  profile_uri: string,
+  bond = '1000000',
+) => {
+  return cm.executeContract(
+    contractAddress,
+    JSON.stringify({
+      register_auditor: {
+        profile_uri,
+      }
+    }),
+    [{ amount: bond, denom: 'untrn' }],
+  );
+}
+
+
 
 export const submitApplication = (
   cm: WalletWrapper,
@@ -49,7 +72,8 @@ export const submitApplication = (
   proposal_id: number,
   applicants: ShareHolder[],
   auditors: ShareHolder[],
-  deliver_by: Expiration
+  deliver_by: Expiration,
+  budget: cosmos.base.v1beta1.ICoin[] = [],
 ) => {
   return cm.executeContract(
     contractAddress,
@@ -60,12 +84,62 @@ export const submitApplication = (
           applicants,
           auditors,
           deliver_by,
+          budget,
         }
       }
     }),
   );
 }
 
+export const openSelection = (
+  cm: WalletWrapper,
+  contractAddress: string,
+  proposal_id: number,
+  ends: Expiration,
+  winners: number,
+) => {
+  return cm.executeContract(
+    contractAddress,
+    JSON.stringify({
+      open_selection: {
+        proposal_id,
+        ends,
+        winners,
+      }
+    }),
+  );
+}
+
+export const selectApplications = (
+  cm: WalletWrapper,
+  contractAddress: string,
+  proposal_id: number,
+) => {
+  return cm.executeContract(
+    contractAddress,
+    JSON.stringify({
+      select_applications: { proposal_id }
+    }),
+  );
+}
+
+export const openBounty = (
+  cm: WalletWrapper,
+  contractAddress: string,
+  proposal_id: number,
+  auditors: ShareHolder[],
+) => {
+  return cm.executeContract(
+    contractAddress,
+    JSON.stringify({
+      open_bounty: {
+        proposal_id,
+        auditors,
+      }
+    }),
+  );
+}
+
 export const acceptApplication = (
   cm: WalletWrapper,
   contractAddress: string,
@@ -106,6 +180,7 @@ export const registerICA = (
   contractAddress: string,
   proposal_id: number,
   connection_id: string,
+  amount = '2000000',
 ) => {
   return cm.executeContract(
     contractAddress,
@@ -115,10 +190,114 @@ export const registerICA = (
         connection_id
       }
     }),
+    [{ amount, denom: 'untrn' }],
+  );
+}
+
+
+
+export const submitDeliverable = (
+  cm: WalletWrapper,
+  contractAddress: string,
+  proposal_id: number,
+  uri: string,
+  content_hash: string,
+  application_sender?: Addr,
+) => {
+  return cm.executeContract(
+    contractAddress,
+    JSON.stringify({
+      submit_deliverable: {
+        proposal_id,
+        application_sender,
+        uri,
+        content_hash,
+      }
+    }),
+  );
+}
+
+export const postUpdate = (
+  cm: WalletWrapper,
+  contractAddress: string,
+  proposal_id: number,
+  uri: string,
+  summary: string,
+  application_sender?: Addr,
+) => {
+  return cm.executeContract(
+    contractAddress,
+    JSON.stringify({
+      post_update: {
+        proposal_id,
+        application_sender,
+        uri,
+        summary,
+      }
+    }),
   );
 }
 
+export const commentUpdate = (
+  cm: WalletWrapper,
+  contractAddress: string,
+  proposal_id: number,
+  application_sender: Addr,
+  update_id: number,
+  comment: string,
+) => {
+  return cm.executeContract(
+    contractAddress,
+    JSON.stringify({
+      comment_update: {
+        proposal_id,
+        application_sender,
+        update_id,
+        comment,
+      }
+    }),
+  );
+}
+
+export const requestExtension = (
+  cm: WalletWrapper,
+  contractAddress: string,
+  proposal_id: number,
+  deliver_by: Expiration,
+  reason: string,
+  application_sender?: Addr,
+) => {
+  return cm.executeContract(
+    contractAddress,
+    JSON.stringify({
+      request_extension: {
+        proposal_id,
+        application_sender,
+        deliver_by,
+        reason,
+      }
+    }),
+  );
+}
 
+export const voteExtension = (
+  cm: WalletWrapper,
+  contractAddress: string,
+  proposal_id: number,
+  application_sender: Addr,
+  approve: boolean,
+) => {
+  return cm.executeContract(
+    contractAddress,
+    JSON.stringify({
+      vote_extension: {
+        proposal_id,
+        application_sender,
+        approve,
+      }
+    }),
+  );
+}
 
 export const verifyApplication = (
   cm: WalletWrapper,
@@ -174,6 +353,20 @@ export const queryCustodyFunds = (
 
 
 
+export const queryUpdates = (
+  cm: CosmosWrapper,
+  contractAddress: string,
+  proposal_id: number,
+  application_sender: string,
+  start_after?: number,
+  limit?: number,
+) =>
+  cm.queryContract<[number, ProgressUpdate][]>(contractAddress, {
+    updates: { proposal_id, application_sender, start_after, limit }
+  });
+
+
+
 export const queryICA = (
   cm: CosmosWrapper,
   contractAddress: string,
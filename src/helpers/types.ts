@@ -241,16 +241,29 @@ export type ProposalInfo = {
   description: string;
   funding: any[];
   applications: [string, Application][];
+  selection?: Selection | null;
+  bounty?: Bounty | null;
+}
+
+export type Bounty = {
+  auditors: GoodFee[];
+}
+
+export type Selection = {
+  ends: Expiration;
+  winners: number;
+  selected?: Addr[] | null;
 }
 
 export type AllProposalInfo = {
   proposals: ProposalInfo[];
+  next_start_after?: number | null;
 }
 
 
 export type ShareHolder = { 
   recipient: Addr, 
-  percent_share: number 
+  share_bps: number
 }
 
 
@@ -290,9 +303,30 @@ export interface Application {
   auditors: GoodFee[];
   deliver_by: Expiration;
   verifications: Addr[];
+  expired: boolean;
+  deliverables: Deliverable[];
+  budget: cosmos.base.v1beta1.ICoin[];
+}
+export interface Deliverable {
+  uri: string;
+  content_hash: string;
+  submitted_by: Addr;
+  submitted_at: Timestamp;
+}
+export interface ProgressUpdate {
+  uri: string;
+  summary: string;
+  posted_by: Addr;
+  posted_at: Timestamp;
+  comments: UpdateComment[];
+}
+export interface UpdateComment {
+  auditor: Addr;
+  comment: string;
+  posted_at: Timestamp;
 }
 export interface GoodFee {
-  percent_share: number;
+  share_bps: number;
   recipient: Addr;
 }
 export type Uint128 = string;
@@ -301,10 +335,12 @@ export interface CustodyFunds {
   locked: boolean;
   proposal_id: number;
   remote?: string | null;
+  excess: Uint128;
 }
 export type ExecuteMsg = {
   submit_proposal: {
     description: string;
+    tags?: string[] | null;
     title: string;
   };
 } | {
@@ -376,8 +412,13 @@ export type QueryMsg = {
     sequence_id: number;
   };
 } | {
-  errors_queue: {};
+  errors_queue: {
+    start_after?: number | null;
+    limit?: number | null;
+    kind?: ErrorKind | null;
+  };
 };
+export type ErrorKind = "missing_payload" | "address_mismatch" | "missing_query_deposit" | "unknown_query" | "retries_exhausted" | "legacy";
 export type SudoMsg = {
   response: {
     data: Binary;
@@ -437,4 +478,13 @@ export interface Height {
   revision_height?: number;
   revision_number?: number;
   [k: string]: unknown;
-}
\ No newline at end of file
+}
+
+export type ReputationStats = {
+  submitted: number;
+  accepted: number;
+  delivered: number;
+  expired: number;
+  disputed: number;
+  earned: cosmos.base.v1beta1.ICoin[];
+}
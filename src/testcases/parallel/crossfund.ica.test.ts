@@ -105,7 +105,7 @@ describe('Neutron Hackaton Test', () => {
         // @ts-ignore
         expect(channels.channels).toIncludeAllPartialMembers([
           {
-            port_id: `icacontroller-${contractAddress}.${proposal_id}`,
+            port_id: `icacontroller-${contractAddress}.${proposal_id}-${connectionId}`,
           },
         ])
       });
@@ -4,7 +4,7 @@ import {
   WalletWrapper,
 } from '../../helpers/cosmos';
 import { TestStateLocalCosmosTestNet } from '../common_localcosmosnet';
-import { acceptApplication, approveApplication, createProposal, fundProposal, queryCustodyFunds, queryProposalById, queryProposals, submitApplication, verifyApplication } from '../../helpers/goods';
+import { acceptApplication, approveApplication, createProposal, fundProposal, queryCustodyFunds, queryProposalById, queryProposals, submitApplication, submitDeliverable, verifyApplication } from '../../helpers/goods';
 
 import { NeutronContract } from '../../helpers/types';
 import { CodeId } from '../../types';
@@ -139,8 +139,8 @@ describe('Neutron Hackaton Test', () => {
             secondAccount, 
             contractAddress,
             0,
-            [{ recipient: secondAccountAddress, percent_share: 99 }],
-            [{ recipient: accountAddress, percent_share: 1 }],
+            [{ recipient: secondAccountAddress, share_bps: 9900 }],
+            [{ recipient: accountAddress, share_bps: 100 }],
             { at_height: currentHeight + 10 },
         )
         expect(res.code).toBe(0);
@@ -198,6 +198,21 @@ describe('Neutron Hackaton Test', () => {
       })
     })
     
+    describe('delivering bounty', () => {
+      test('if can submit a deliverable', async () => {
+        const res = await submitDeliverable(secondAccount, contractAddress, 0, 'ipfs://deliverable', 'ab'.repeat(32))
+        expect(res.code).toBe(0);
+      })
+
+      test('if the deliverable is recorded', async () => {
+        const proposal = await queryProposalById(neutronChain, contractAddress, 0)
+        const [_, application] = proposal.applications[0];
+        expect(application.deliverables).toHaveLength(1);
+        expect(application.deliverables[0].uri).toBe('ipfs://deliverable');
+        expect(application.deliverables[0].submitted_by).toBe(secondAccountAddress);
+      })
+    })
+
     describe('verifying bounty', () => {
       test('if can verify bounty', async () => {
         const proposal = await queryProposalById(neutronChain, contractAddress, 0)